@@ -0,0 +1,33 @@
+use crate::FILE_PATH;
+use axum::response::IntoResponse;
+use feedback_telemetry::health::{readiness, Service};
+use std::fs::{self, OpenOptions};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub use feedback_telemetry::health::{check, healthz};
+
+const SERVICE: Service = Service {
+    name: env!("CARGO_PKG_NAME"),
+    version: env!("CARGO_PKG_VERSION"),
+    storage_mode: "read-write",
+    storage_root: FILE_PATH,
+};
+/// Numbers the probe files, so overlapping probes never remove each other's file
+static PROBES: AtomicU64 = AtomicU64::new(0);
+
+pub async fn readyz() -> impl IntoResponse {
+    // Create (and remove) a file to find out if the volume is mounted and writable
+    let probe = format!("{FILE_PATH}.readyz-{}-{}", process::id(), PROBES.fetch_add(1, Ordering::Relaxed));
+    let result = OpenOptions::new()
+        .create_new(true)
+        .write(true)
+        .open(&probe)
+        .and_then(|_| fs::remove_file(&probe));
+
+    readiness(result, FILE_PATH, "writable")
+}
+
+pub async fn version() -> impl IntoResponse {
+    feedback_telemetry::health::version(SERVICE)
+}
@@ -1,23 +1,35 @@
+mod health;
+mod metrics;
+mod redact;
+
 use anyhow::{Context, Result};
-use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::extract::rejection::JsonRejection;
+use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::routing::post;
-use axum::{Json, Router};
+use axum::routing::get;
+use axum::Json;
 use chrono::Utc;
+use feedback_telemetry::cors::CorsPolicy;
 use serde::Deserialize;
 use std::env;
 use std::fs::OpenOptions;
 use std::io::{BufWriter, Write};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
 use tokio::net::TcpListener;
-use tower_http::cors::CorsLayer;
-use tracing::{debug, error, info, subscriber, Level};
-use tracing_subscriber::FmtSubscriber;
+use tracing::{debug, error, info, warn, Level};
+use crate::metrics::METRICS;
+use crate::redact::REDACTOR;
 
 static WRITE_MUTEX: Mutex<()> = Mutex::new(());
 const FILE_PATH: &str = "/feedback/";
 const FILE_NAME: &str = "feedback.txt";
+/// Also locked by feedback_review_backend while it rewrites a day file to delete an entry
+const LOCK_FILE: &str = ".write.lock";
+/// Optional line after the timestamp, e.g. `[meta redacted=email,phone]`
+const META_PREFIX: &str = "[meta ";
+const LINE_SEP_LEN: usize = 50;
 
 const PORT: u16 = 8080; // This only runs in docker, so 8080 works
 static LOG_LEVEL: LazyLock<Level> = LazyLock::new(|| {
@@ -47,25 +59,27 @@ struct Feedback {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    subscriber::set_global_default(
-        FmtSubscriber::builder()
-            .with_max_level(*LOG_LEVEL)
-            .finish()
-    ).with_context(|| format!("Failed to set up logging with level {}", *LOG_LEVEL))?;
-
-    let cors = CorsLayer::new()
-        .allow_origin(
-            env::var("ALLOW_ORIGIN")
-                .context("ALLOW_ORIGIN env var is not set")?
-                .parse::<HeaderValue>()
-                .context("Failed to parse origin")?
-        )
-        .allow_methods([Method::POST, Method::OPTIONS])
-        .allow_headers([header::CONTENT_TYPE]);
-
-    let app = Router::new()
-        .route("/feedback", post(handle_feedback))
-        .layer(cors);
+    if env::args().nth(1).as_deref() == Some("healthcheck") {
+        return health::check(PORT).await;
+    }
+
+    let _telemetry = feedback_telemetry::init(*LOG_LEVEL, env!("CARGO_PKG_NAME"))?;
+
+    let cors = CorsPolicy::from_env()?;
+
+    info!("Redaction mode {:?} with detectors {:?}", REDACTOR.mode(), REDACTOR.detector_names());
+
+    let app = cors.router()
+        .post("/feedback", handle_feedback)
+        .into_router()
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/version", get(health::version));
+
+    // Registers the metrics, so they are listed before anything was counted
+    LazyLock::force(&metrics::METRICS);
+    let app = feedback_telemetry::metrics::mount(app).await?;
+    let app = feedback_telemetry::trace_requests(app);
 
     let listener = TcpListener::bind(
         SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), PORT)
@@ -82,14 +96,51 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn handle_feedback(Json(feedback): Json<Feedback>) -> impl IntoResponse {
-    const LINE_SEP_LEN: usize = 50;
+async fn handle_feedback(feedback: Result<Json<Feedback>, JsonRejection>) -> impl IntoResponse {
+    let feedback = match feedback {
+        Ok(Json(feedback)) => feedback,
+        Err(e) => {
+            warn!("Rejected invalid feedback: {e}");
+            METRICS.rejected("invalid_body");
+            return (e.status(), "Invalid feedback");
+        }
+    };
+
+    // Locking waits for feedback_review_backend to finish rewriting a day file,
+    // which must not block a runtime thread
+    match tokio::task::spawn_blocking(move || append_feedback(&feedback.feedback)).await {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Failed to run the write task: {e}");
+            reject("task", "Failed to write feedback")
+        }
+    }
+}
+
+/// Redacts the feedback and appends it to today's file while holding both write locks
+fn append_feedback(feedback: &str) -> (StatusCode, &'static str) {
     let Ok(_lock) = WRITE_MUTEX.lock() else {
         error!("Failed to acquire write lock");
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to acquire write lock");
+        return reject("lock", "Failed to acquire write lock");
     };
+    let lock_file = format!("{FILE_PATH}{LOCK_FILE}");
+    let Ok(_file_lock) = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_file)
+        .and_then(|file| file.lock().map(|()| file)) else {
+        error!("Failed to lock {lock_file}");
+        return reject("lock", "Failed to acquire write lock");
+    };
+    let start = Instant::now();
+
+    let redaction = REDACTOR.redact(feedback);
+    if !redaction.fired.is_empty() {
+        info!("Redacted {:?} from feedback", redaction.fired);
+    }
 
-    info!(?feedback);
+    info!(feedback = redaction.text);
 
     let now = Utc::now();
     let current_date = now.format("%Y-%m-%d");
@@ -99,13 +150,13 @@ async fn handle_feedback(Json(feedback): Json<Feedback>) -> impl IntoResponse {
 
     let Ok(file) = OpenOptions::new()
         .create(true)
-        .write(true)
         .append(true)
         .open(&file_name) else {
         error!("Failed to open file {file_name} \
             (you probably didn't bind the correct port in docker)");
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to open file");
+        return reject("open", "Failed to open file");
     };
+    let initial_len = file.metadata().map(|meta| meta.len()).unwrap_or_default();
 
     debug!("Opened file");
 
@@ -115,29 +166,77 @@ async fn handle_feedback(Json(feedback): Json<Feedback>) -> impl IntoResponse {
 
     if let Err(e) = writeln!(writer, "{}", "-".repeat(LINE_SEP_LEN)) {
         error!("Failed to write initial lines to file {file_name}: {e}");
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to write to file");
+        return reject("write", "Failed to write to file");
     };
     if let Err(e) = writeln!(writer, "{current_date_time}") {
         error!("Failed to write time to file {file_name}: {e}");
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to write to file");
+        return reject("write", "Failed to write to file");
+    };
+    if !redaction.fired.is_empty()
+        && let Err(e) = writeln!(writer, "{META_PREFIX}redacted={}]", redaction.fired.join(",")) {
+        error!("Failed to write metadata to file {file_name}: {e}");
+        return reject("write", "Failed to write to file");
     };
-    if let Err(e) = writeln!(writer, "{}", feedback.feedback) {
+    if let Err(e) = writeln!(writer, "{}", escape_body(&redaction.text)) {
         error!("Failed to write feedback to file {file_name}: {e}");
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to write to file");
+        return reject("write", "Failed to write to file");
     };
     if let Err(e) = writeln!(writer, "{}\n", "-".repeat(LINE_SEP_LEN)) {
         error!("Failed to write ending lines to file {file_name}: {e}");
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to write to file");
+        return reject("write", "Failed to write to file");
     };
 
     debug!("Finished writing, flushing writer");
 
     if let Err(e) = writer.flush() {
         error!("Failed to flush file {file_name}: {e}");
-        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to flush file");
+        return reject("flush", "Failed to flush file");
     };
 
+    let written = writer.get_ref()
+                        .metadata()
+                        .map(|meta| meta.len().saturating_sub(initial_len))
+                        .unwrap_or_default();
+    METRICS.accepted(&current_date.to_string(), written, start.elapsed().as_secs_f64());
+
     debug!("Exiting");
 
     (StatusCode::OK, "Feedback Received")
 }
+
+/// Submitted lines that would read as metadata or as the end of the entry get one more leading
+/// backslash, feedback_review_backend strips one again. Every other line is written as it is.
+fn escape_body(text: &str) -> String {
+    let separator = "-".repeat(LINE_SEP_LEN);
+    text.split('\n')
+        .map(|line| {
+            let unescaped = line.trim_start_matches('\\');
+            if unescaped.starts_with(META_PREFIX) || unescaped.trim_end_matches('\r') == separator {
+                format!("\\{line}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn reject(reason: &str, msg: &'static str) -> (StatusCode, &'static str) {
+    METRICS.rejected(reason);
+    (StatusCode::INTERNAL_SERVER_ERROR, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_reserved_lines() {
+        let dashes = "-".repeat(LINE_SEP_LEN);
+        let text = format!("[meta deleted=2024-05-01T00:00:00Z]\nfine [meta x=y]\n{dashes}\n\\[meta already]\n\\plain");
+        assert_eq!(
+            escape_body(&text),
+            format!("\\[meta deleted=2024-05-01T00:00:00Z]\nfine [meta x=y]\n\\{dashes}\n\\\\[meta already]\n\\plain"),
+        );
+    }
+}
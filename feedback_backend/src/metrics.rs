@@ -0,0 +1,50 @@
+use feedback_telemetry::metrics::REGISTRY;
+use prometheus::{Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts};
+use std::sync::LazyLock;
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    accepted: IntCounter,
+    rejected: IntCounterVec,
+    write_latency: Histogram,
+    bytes_written: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let accepted = IntCounter::new(
+            "feedback_submissions_accepted_total",
+            "Feedback submissions written to disk",
+        ).unwrap();
+        let rejected = IntCounterVec::new(
+            Opts::new("feedback_submissions_rejected_total", "Feedback submissions that were not written"),
+            &["reason"],
+        ).unwrap();
+        let write_latency = Histogram::with_opts(
+            HistogramOpts::new("feedback_write_duration_seconds", "Time spent writing a submission to disk")
+                .buckets(vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]),
+        ).unwrap();
+        let bytes_written = IntCounterVec::new(
+            Opts::new("feedback_bytes_written_total", "Bytes appended to the feedback file of a day"),
+            &["day"],
+        ).unwrap();
+
+        REGISTRY.register(Box::new(accepted.clone())).unwrap();
+        REGISTRY.register(Box::new(rejected.clone())).unwrap();
+        REGISTRY.register(Box::new(write_latency.clone())).unwrap();
+        REGISTRY.register(Box::new(bytes_written.clone())).unwrap();
+
+        Self { accepted, rejected, write_latency, bytes_written }
+    }
+
+    pub fn accepted(&self, day: &str, bytes: u64, seconds: f64) {
+        self.accepted.inc();
+        self.bytes_written.with_label_values(&[day]).inc_by(bytes);
+        self.write_latency.observe(seconds);
+    }
+
+    pub fn rejected(&self, reason: &str) {
+        self.rejected.with_label_values(&[reason]).inc();
+    }
+}
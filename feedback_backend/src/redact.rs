@@ -0,0 +1,272 @@
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::sync::LazyLock;
+
+/// The redactor used by `handle_feedback`, configured from the environment
+pub static REDACTOR: LazyLock<Redactor> = LazyLock::new(Redactor::from_env);
+
+const HASH_LEN: usize = 12;
+
+/// Dates that would otherwise pass as grouped phone numbers, e.g. `2024-05-01` or `01/05/2024`
+static DATE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d{4}-\d{1,2}-\d{1,2}|\d{1,2}/\d{1,2}/\d{2,4}").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Off,
+    Mask,
+    Hash,
+}
+
+struct Detector {
+    name: &'static str,
+    regex: Regex,
+    is_match: fn(&str) -> bool,
+}
+
+pub struct Redactor {
+    mode: Mode,
+    salt: String,
+    detectors: Vec<Detector>,
+}
+
+/// The redacted text and the names of all detectors that matched at least once
+#[derive(Debug)]
+pub struct Redaction {
+    pub text: String,
+    pub fired: Vec<&'static str>,
+}
+
+impl Redactor {
+    fn from_env() -> Self {
+        const MODE_KEY: &str = "REDACT_MODE";
+        const SALT_KEY: &str = "REDACT_HASH_SALT";
+        const DEFAULT_MODE: Mode = Mode::Mask;
+
+        let mode = match env::var(MODE_KEY).as_deref() {
+            Ok("off") => Mode::Off,
+            Ok("mask") => Mode::Mask,
+            Ok("hash") => Mode::Hash,
+            Ok(_) => {
+                println!("WARNING: {MODE_KEY} is set, but the value is invalid, \
+                    using default ({DEFAULT_MODE:?})");
+                DEFAULT_MODE
+            }
+            Err(_) => DEFAULT_MODE,
+        };
+
+        let detectors = [
+            ("REDACT_EMAIL", "email", Detector::email as fn() -> Detector),
+            ("REDACT_IBAN", "iban", Detector::iban),
+            ("REDACT_PHONE", "phone", Detector::phone),
+        ].into_iter()
+         .filter(|(key, name, _)| enabled(key, name))
+         .map(|(_, _, detector)| detector())
+         .collect();
+
+        Self { mode, salt: env::var(SALT_KEY).unwrap_or_default(), detectors }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn detector_names(&self) -> Vec<&'static str> {
+        self.detectors.iter().map(|d| d.name).collect()
+    }
+
+    /// Replaces every match of an enabled detector.
+    /// All detectors run on the original text, if two matches overlap,
+    /// the detector listed first wins (e.g. an IBAN is never half redacted as a phone number).
+    pub fn redact(&self, text: &str) -> Redaction {
+        if self.mode == Mode::Off {
+            return Redaction { text: text.to_string(), fired: vec![] };
+        }
+
+        let mut matches: Vec<(usize, usize, &'static str)> = vec![];
+        for detector in &self.detectors {
+            for m in detector.regex.find_iter(text) {
+                if !(detector.is_match)(m.as_str()) { continue; }
+                if matches.iter().any(|&(start, end, _)| m.start() < end && start < m.end()) {
+                    continue;
+                }
+                matches.push((m.start(), m.end(), detector.name));
+            }
+        }
+        matches.sort_unstable_by_key(|&(start, _, _)| start);
+
+        let mut redacted = String::with_capacity(text.len());
+        let mut fired = vec![];
+        let mut last = 0;
+        for (start, end, name) in matches {
+            redacted.push_str(&text[last..start]);
+            redacted.push_str(&self.replacement(name, &text[start..end]));
+            if !fired.contains(&name) {
+                fired.push(name);
+            }
+            last = end;
+        }
+        redacted.push_str(&text[last..]);
+
+        Redaction { text: redacted, fired }
+    }
+
+    fn replacement(&self, name: &str, found: &str) -> String {
+        match self.mode {
+            Mode::Hash => {
+                let mut hasher = Sha256::new();
+                hasher.update(self.salt.as_bytes());
+                hasher.update(found.as_bytes());
+                let hash = hasher.finalize()
+                                 .iter()
+                                 .map(|b| format!("{b:02x}"))
+                                 .collect::<String>();
+                format!("[{name}:{}]", &hash[..HASH_LEN])
+            }
+            Mode::Mask | Mode::Off => format!("[redacted {name}]"),
+        }
+    }
+}
+
+impl Detector {
+    fn email() -> Self {
+        Self {
+            name: "email",
+            regex: Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b").unwrap(),
+            is_match: |_| true,
+        }
+    }
+
+    fn iban() -> Self {
+        Self {
+            name: "iban",
+            regex: Regex::new(r"(?i)\b[a-z]{2}\d{2}(?: ?[a-z0-9]{4}){2,7}(?: ?[a-z0-9]{1,3})?\b").unwrap(),
+            is_match: valid_iban,
+        }
+    }
+
+    /// Only phone shaped numbers: with a country code (`+49 30 1234567`, `0049 ...`),
+    /// an area code in parentheses (`(030) 1234567`) or a trunk prefix and grouping (`030 1234567`).
+    /// Dots aren't accepted as separators, so IP addresses and version strings never match,
+    /// and plain digit runs like order numbers have none of these shapes.
+    fn phone() -> Self {
+        Self {
+            name: "phone",
+            regex: Regex::new(
+                r"(?x)
+                (?:\+|\b00)\d{1,3}[\ /-]?(?:\(0\)\ ?)?\d[\d\ /-]{4,}\d\b
+                | \(0?\d{2,5}\)\ ?\d[\d\ /-]{3,}\d\b
+                | \b0\d{2,5}[\ /-]\d[\d\ /-]{2,}\d\b
+                ",
+            ).unwrap(),
+            is_match: |found| {
+                (7..=15).contains(&found.chars().filter(char::is_ascii_digit).count()) && !DATE.is_match(found)
+            },
+        }
+    }
+}
+
+fn enabled(key: &str, name: &str) -> bool {
+    match env::var(key).as_deref() {
+        Ok("true") | Err(_) => true,
+        Ok("false") => false,
+        Ok(_) => {
+            println!("WARNING: {key} is set, but the value is invalid, \
+                keeping the {name} detector enabled");
+            true
+        }
+    }
+}
+
+/// ISO 13616 check: move the first four characters to the end,
+/// replace letters with numbers (A = 10, ..) and the result mod 97 has to be 1
+fn valid_iban(found: &str) -> bool {
+    let iban = found.chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect::<Vec<_>>();
+
+    if !(15..=34).contains(&iban.len()) { return false; }
+
+    let mut remainder = 0u32;
+    for c in iban[4..].iter().chain(&iban[..4]) {
+        let Some(value) = c.to_digit(36) else { return false; };
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+    }
+
+    remainder == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(detector: fn() -> Detector) -> Redactor {
+        Redactor { mode: Mode::Mask, salt: String::new(), detectors: vec![detector()] }
+    }
+
+    fn assert_redacts(detector: fn() -> Detector, text: &str, expected: &str) {
+        let redaction = redactor(detector).redact(text);
+        assert_eq!(redaction.text, expected, "redacting {text:?}");
+    }
+
+    fn assert_keeps(detector: fn() -> Detector, text: &str) {
+        let redaction = redactor(detector).redact(text);
+        assert_eq!(redaction.text, text, "redacting {text:?}");
+        assert!(redaction.fired.is_empty());
+    }
+
+    #[test]
+    fn redacts_emails() {
+        assert_redacts(Detector::email, "mail me at jane.doe+test@example.co.uk", "mail me at [redacted email]");
+        assert_redacts(Detector::email, "A@B.DE or c@d.org", "[redacted email] or [redacted email]");
+
+        assert_keeps(Detector::email, "ping @support on example.com");
+        assert_keeps(Detector::email, "user@localhost");
+    }
+
+    #[test]
+    fn redacts_ibans() {
+        assert_redacts(Detector::iban, "DE89 3704 0044 0532 0130 00 please", "[redacted iban] please");
+        assert_redacts(Detector::iban, "GB82WEST12345698765432", "[redacted iban]");
+
+        // Wrong check digits
+        assert_keeps(Detector::iban, "DE00 3704 0044 0532 0130 00");
+        assert_keeps(Detector::iban, "ab12 cdef ghij");
+    }
+
+    #[test]
+    fn redacts_phone_numbers() {
+        assert_redacts(Detector::phone, "call +49 30 1234567 now", "call [redacted phone] now");
+        assert_redacts(Detector::phone, "0049 (0) 30 123 4567", "[redacted phone]");
+        assert_redacts(Detector::phone, "Tel. (030) 1234567", "Tel. [redacted phone]");
+        assert_redacts(Detector::phone, "mobile 0151-12345678", "mobile [redacted phone]");
+        assert_redacts(Detector::phone, "030/123 45 67", "[redacted phone]");
+    }
+
+    #[test]
+    fn keeps_numbers_that_are_not_phone_numbers() {
+        assert_keeps(Detector::phone, "since 2024-05-01 10:30");
+        assert_keeps(Detector::phone, "on 01/05/2024 at 9");
+        assert_keeps(Detector::phone, "from 192.168.100.200");
+        assert_keeps(Detector::phone, "version 1.23.456.7890");
+        assert_keeps(Detector::phone, "order 123456789 is late");
+        assert_keeps(Detector::phone, "+49 123");
+    }
+
+    #[test]
+    fn hashes_with_salt() {
+        let mut redactor = redactor(Detector::email);
+        redactor.mode = Mode::Hash;
+        let first = redactor.redact("a@b.de").text;
+        assert!(first.starts_with("[email:") && first.len() == "[email:]".len() + HASH_LEN);
+        assert_eq!(redactor.redact("a@b.de").text, first);
+
+        redactor.salt = String::from("pepper");
+        assert_ne!(redactor.redact("a@b.de").text, first);
+    }
+}
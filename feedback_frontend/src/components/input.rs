@@ -56,9 +56,13 @@ pub fn input(props: &InputProps) -> Html {
                         feedback.set(String::new());
                     }
                     Ok(resp) => {
+                        // The request id lets us find the failed write in the backend logs
+                        let request_id = resp.headers()
+                                             .get("x-request-id")
+                                             .unwrap_or_else(|| String::from("unknown"));
                         thanks_colour.set(Colour::Orange);
-                        thanks_msg.set(Some(format!("Backend was unable to handle request: \
-                            {resp:?}")))
+                        thanks_msg.set(Some(format!("Backend was unable to handle request \
+                            (request id {request_id}): {resp:?}")))
                     }
                     Err(e) => {
                         thanks_colour.set(Colour::Red);
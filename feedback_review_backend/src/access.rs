@@ -0,0 +1,312 @@
+use crate::audit::AuditLog;
+use crate::auth::Identity;
+use crate::storage::{DayDate, InvalidInput};
+use crate::{EntriesError, DATA_ROOT};
+use axum::body::{Body, HttpBody};
+use axum::extract::{MatchedPath, Query, RawPathParams, Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::env;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
+use tokio::runtime::Handle;
+use tracing::{debug, error};
+
+const ACCESS_FILE: &str = "access.log";
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+/// Parameters that are ids, dates or paging and logged as they are.
+/// Anything else may be free text that quotes feedback, like the search `q`, so only its presence is logged.
+const LOGGED_PARAMS: &[&str] = &[
+    "date", "index", "comment", "from", "to", "day", "cursor", "limit", "offset", "tag", "format", "done", "user", "route",
+];
+const REDACTED: &str = "[redacted]";
+/// Past this size the log is rotated, so at most about twice as much is kept
+static MAX_SIZE_MB: LazyLock<u64> = LazyLock::new(|| {
+    const ENV_KEY: &str = "ACCESS_LOG_MAX_MB";
+    const DEFAULT_MB: u64 = 64;
+
+    match env::var(ENV_KEY) {
+        Ok(mb) => {
+            mb.parse()
+              .ok()
+              .filter(|mb| *mb > 0)
+              .unwrap_or_else(|| {
+                  println!("WARNING: {ENV_KEY} is set, but the value is invalid, \
+                      using default ({DEFAULT_MB})");
+                  DEFAULT_MB
+              })
+        }
+        Err(_) => DEFAULT_MB,
+    }
+});
+
+/// An [`AuditLog`] of every authenticated request, kept apart from the deletions
+pub struct AccessLog(AuditLog);
+
+/// One authenticated request. Request bodies are left out, they may quote feedback.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessEvent {
+    pub at: DateTime<Utc>,
+    pub user: String,
+    pub method: String,
+    /// The route pattern, e.g. `/feedback/{date}/entries`
+    pub route: String,
+    /// Path parameters and the query string, free text values are replaced by `[redacted]`
+    pub params: BTreeMap<String, String>,
+    pub status: u16,
+    /// Bytes of the response body, streamed bodies are counted until the client stopped reading
+    pub size: u64,
+}
+
+/// All filters are optional. `day` matches requests for that `{date}` and requests whose
+/// `from`/`to` parameters include it. `from` and `to` are inclusive and compared with the time of the request.
+#[derive(Debug, Deserialize)]
+pub struct AccessQuery {
+    user: Option<String>,
+    route: Option<String>,
+    day: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<String>,
+    offset: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AccessPage {
+    /// Newest first
+    events: Vec<AccessEvent>,
+    /// Matching events before `limit` and `offset`
+    total: usize,
+}
+
+impl AccessLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self(AuditLog::rotating(path, *MAX_SIZE_MB * 1024 * 1024))
+    }
+}
+
+impl Default for AccessLog {
+    fn default() -> Self {
+        Self::new(PathBuf::from(DATA_ROOT).join(ACCESS_FILE))
+    }
+}
+
+impl AccessEvent {
+    /// Whether the request was about `day`, requests without any bounds aren't counted
+    fn covers(&self, day: DayDate) -> bool {
+        let param = |key: &str| self.params.get(key).and_then(|value| value.parse::<DayDate>().ok());
+        if let Some(date) = param("date") {
+            return date == day;
+        }
+
+        let (from, to) = (param("from"), param("to"));
+        (from.is_some() || to.is_some())
+            && from.is_none_or(|from| from <= day)
+            && to.is_none_or(|to| day <= to)
+    }
+}
+
+/// Logged when dropped, that is once the whole body was sent or the client went away
+struct Pending {
+    log: Arc<AccessLog>,
+    event: AccessEvent,
+}
+
+impl Pending {
+    fn count(&mut self, bytes: usize) {
+        self.event.size += bytes as u64;
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        write(self.log.clone(), self.event.clone());
+    }
+}
+
+/// Recording waits until the event is on disk, the response doesn't wait for that
+fn write(log: Arc<AccessLog>, event: AccessEvent) {
+    match Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(async move {
+                if let Err(e) = log.0.record(&event).await {
+                    error!(?event, "Failed to write the access log: {e}");
+                }
+            });
+        }
+        Err(_) => error!(?event, "Failed to write the access log: no runtime"),
+    }
+}
+
+/// Runs after [`crate::auth::require_auth`], so every request it sees has an [`Identity`]
+pub async fn track(State(log): State<Arc<AccessLog>>, req: Request, next: Next) -> Response {
+    let user = req.extensions()
+                  .get::<Identity>()
+                  .map(|identity| identity.name.clone())
+                  .unwrap_or_default();
+    let route = req.extensions()
+                   .get::<MatchedPath>()
+                   .map_or_else(|| req.uri().path().to_string(), |path| path.as_str().to_string());
+    let method = req.method().to_string();
+
+    let (mut parts, body) = req.into_parts();
+    let mut params = Query::<Vec<(String, String)>>::try_from_uri(&parts.uri)
+        .map(|Query(params)| params.into_iter().collect::<BTreeMap<_, _>>())
+        .unwrap_or_default();
+    if let Ok(path_params) = axum::RequestPartsExt::extract::<RawPathParams>(&mut parts).await {
+        params.extend(path_params.iter().map(|(key, value)| (key.to_string(), value.to_string())));
+    }
+    for (key, value) in &mut params {
+        if !LOGGED_PARAMS.contains(&key.as_str()) {
+            *value = REDACTED.to_string();
+        }
+    }
+    let req = Request::from_parts(parts, body);
+
+    let response = next.run(req).await;
+    let (parts, body) = response.into_parts();
+    let mut event = AccessEvent {
+        at: Utc::now(),
+        user,
+        method,
+        route,
+        params,
+        status: parts.status.as_u16(),
+        size: 0,
+    };
+
+    if let Some(size) = body.size_hint().exact() {
+        event.size = size;
+        write(log, event);
+        return Response::from_parts(parts, body);
+    }
+
+    // Streamed, the event is written once the stream is dropped
+    let mut pending = Pending { log, event };
+    let body = body.into_data_stream().inspect(move |chunk| {
+        if let Ok(chunk) = chunk {
+            pending.count(chunk.len());
+        }
+    });
+    Response::from_parts(parts, Body::from_stream(body))
+}
+
+pub async fn get_access_log(State(log): State<Arc<AccessLog>>, Query(query): Query<AccessQuery>) -> Response {
+    debug!(?query, "Getting access log");
+
+    let day = match DayDate::parse_param(query.day.as_deref()) {
+        Ok(day) => day,
+        Err(e) => return e.into_response(),
+    };
+    let (from, to) = match (DayDate::parse_param(query.from.as_deref()), DayDate::parse_param(query.to.as_deref())) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(e), _) | (_, Err(e)) => return e.into_response(),
+    };
+    let limit = match crate::parse_limit(query.limit.as_deref(), DEFAULT_LIMIT, MAX_LIMIT) {
+        Ok(limit) => limit,
+        Err(e) => return e.into_response(),
+    };
+    let offset = match query.offset.as_deref().map(str::parse::<usize>) {
+        None => 0,
+        Some(Ok(offset)) => offset,
+        Some(Err(e)) => return InvalidInput::new("invalid_offset", e.to_string(), query.offset.unwrap_or_default())
+            .into_response(),
+    };
+
+    // Only the newest matches up to the requested page are kept while reading
+    let window = offset.saturating_add(limit);
+    let mut newest = VecDeque::new();
+    let mut total = 0;
+    let visited = log.0.visit(|event: AccessEvent| {
+        let matches = query.user.as_ref().is_none_or(|user| event.user == *user)
+            && query.route.as_ref().is_none_or(|route| event.route == *route)
+            && day.is_none_or(|day| event.covers(day))
+            && from.is_none_or(|from| event.at.date_naive() >= from.as_naive())
+            && to.is_none_or(|to| event.at.date_naive() <= to.as_naive());
+        if matches {
+            total += 1;
+            newest.push_back(event);
+            if newest.len() > window {
+                newest.pop_front();
+            }
+        }
+    }).await;
+    if let Err(e) = visited {
+        error!("Failed to read the access log: {e}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response();
+    }
+
+    let events = newest.into_iter().rev().skip(offset).take(limit).collect();
+
+    Json(AccessPage { events, total }).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{app, app_as, fetch, temp_root};
+    use axum::http::StatusCode;
+    use std::path::Path;
+    use std::time::Duration;
+
+    async fn wait_for_lines(root: &Path, lines: usize) {
+        let path = root.join("review_data").join(ACCESS_FILE);
+        for _ in 0..100 {
+            if std::fs::read_to_string(&path).is_ok_and(|log| log.lines().count() >= lines) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("The access log never had {lines} lines");
+    }
+
+    #[tokio::test]
+    async fn logs_access() {
+        let root = temp_root("access");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-05-01{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-01 - 12:00:00]z\nfirst\n{dashes}\n\n"),
+        ).unwrap();
+
+        // Written in the background after each response, waiting keeps them in order
+        let (_, entries) = fetch(app_as(&root, "alice"), "/feedback/2024-05-01/entries").await;
+        wait_for_lines(&root, 1).await;
+        fetch(app_as(&root, "bob"), "/search?q=first&from=2024-04-01").await;
+        wait_for_lines(&root, 2).await;
+        fetch(app_as(&root, "bob"), "/feedback/2024-05-02").await;
+        wait_for_lines(&root, 3).await;
+
+        let (status, body) = fetch(app(&root), "/audit/access?day=2024-05-01").await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let page: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(page["total"], 2);
+        assert_eq!(page["events"][0]["user"], "bob");
+        assert_eq!(page["events"][0]["route"], "/search");
+        assert_eq!(page["events"][0]["params"]["q"], REDACTED);
+        assert_eq!(page["events"][0]["params"]["from"], "2024-04-01");
+        assert_eq!(page["events"][1]["user"], "alice");
+        assert_eq!(page["events"][1]["route"], "/feedback/{date}/entries");
+        assert_eq!(page["events"][1]["params"]["date"], "2024-05-01");
+        assert_eq!(page["events"][1]["status"], 200);
+        assert_eq!(page["events"][1]["size"], entries.len());
+
+        let (_, body) = fetch(app(&root), "/audit/access?user=bob&limit=1").await;
+        let page: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(page["total"], 2);
+        assert_eq!(page["events"][0]["status"], 404);
+
+        let (status, _) = fetch(app(&root), "/audit/access?day=yesterday").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
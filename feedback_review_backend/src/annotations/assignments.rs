@@ -0,0 +1,207 @@
+use super::{check_entry, update_failed, Annotation, Annotations, EntryIndex};
+use crate::auth::{Auth, Identity};
+use crate::days::{Day, DayIndex};
+use crate::feedback::Entry;
+use crate::storage::{DayDate, InvalidInput};
+use crate::EntriesError;
+use axum::extract::{Extension, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, error, info};
+
+/// Who follows up on an entry, and until when
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assignment {
+    /// A reviewer account
+    pub assignee: String,
+    pub due: Option<DayDate>,
+    pub by: String,
+    pub at: DateTime<Utc>,
+}
+
+/// `assignee: null` removes the assignment
+#[derive(Debug, Deserialize)]
+pub struct AssignmentUpdate {
+    assignee: Option<String>,
+    due: Option<String>,
+}
+
+/// `done=true` includes resolved and archived entries
+#[derive(Debug, Deserialize)]
+pub struct AssignmentsQuery {
+    #[serde(default)]
+    done: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct AssignedEntry {
+    entry: Entry,
+    annotation: Annotation,
+    /// Past the due date and not resolved or archived yet
+    overdue: bool,
+}
+
+impl AssignedEntry {
+    fn due(&self) -> Option<DayDate> {
+        self.annotation.assignment.as_ref().and_then(|assignment| assignment.due)
+    }
+}
+
+pub async fn set_assignment(
+    State(days): State<Arc<DayIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    State(auth): State<Arc<Auth>>,
+    Extension(identity): Extension<Identity>,
+    date: DayDate,
+    EntryIndex(index): EntryIndex,
+    Json(update): Json<AssignmentUpdate>,
+) -> Response {
+    let due = match DayDate::parse_param(update.due.as_deref()) {
+        Ok(due) => due,
+        Err(e) => return e.into_response(),
+    };
+    if let Some(assignee) = &update.assignee
+        && !auth.is_reviewer(assignee).await {
+        return InvalidInput::new("invalid_assignee", "Only reviewer accounts can be assigned", assignee.as_str())
+            .into_response();
+    }
+    if let Err(response) = check_entry(&days, date, index).await {
+        return response;
+    }
+
+    let result = annotations.update(date, index, &identity.name, |annotation| {
+        annotation.assignment = update.assignee.clone().map(|assignee| Assignment {
+            assignee,
+            due,
+            by: identity.name.clone(),
+            at: Utc::now(),
+        });
+        Ok(())
+    }).await;
+
+    match result {
+        Ok(annotation) => {
+            info!(%date, index, assignee = ?update.assignee, ?due, by = identity.name, "Changed assignment");
+            Json(annotation).into_response()
+        }
+        Err(e) => update_failed(e, date, index),
+    }
+}
+
+/// The entries assigned to whoever asks, due first
+pub async fn my_assignments(
+    State(days): State<Arc<DayIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    Extension(identity): Extension<Identity>,
+    Query(query): Query<AssignmentsQuery>,
+) -> Response {
+    debug!(name = identity.name, ?query, "Getting assignments");
+
+    let mut assigned = match annotations.assigned_to(&identity.name).await {
+        Ok(assigned) => assigned,
+        Err(e) => {
+            error!("Failed to read annotations: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response();
+        }
+    };
+    assigned.retain(|(_, _, annotation)| query.done || !annotation.status.is_done());
+    assigned.sort_by_key(|(date, index, _)| (*date, *index));
+
+    let today = DayDate::today().as_naive();
+    let mut result = Vec::with_capacity(assigned.len());
+    // Sorted by date, so every day is looked up once
+    let mut day: Option<(DayDate, Option<Arc<Day>>)> = None;
+    for (date, index, annotation) in assigned {
+        if day.as_ref().is_none_or(|(loaded, _)| *loaded != date) {
+            let entries = match days.day(date).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    error!("Failed to read feedback for date {date}: {e}");
+                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response();
+                }
+            };
+            day = Some((date, entries));
+        }
+
+        // The entry may have been deleted since it was assigned
+        let Some(entry) = day.as_ref()
+                            .and_then(|(_, day)| day.as_ref())
+                            .and_then(|day| day.entries.iter().find(|entry| entry.index == index)) else {
+            continue;
+        };
+        let overdue = !annotation.status.is_done()
+            && annotation.assignment
+                         .as_ref()
+                         .and_then(|assignment| assignment.due)
+                         .is_some_and(|due| due.as_naive() < today);
+        result.push(AssignedEntry { entry: entry.clone(), annotation, overdue });
+    }
+
+    // Entries without a due date last
+    result.sort_by_key(|assigned| (assigned.due().is_none(), assigned.due()));
+    Json(result).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{app, app_as, fetch, send, temp_root};
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn lists_assignments() {
+        let root = temp_root("assignments");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-05-01{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-01 - 12:00:00]z\nfirst\n{dashes}\n\n{dashes}\n[2024-05-01 - 13:00:00]z\nsecond\n{dashes}\n\n"),
+        ).unwrap();
+        std::fs::create_dir_all(root.join("review_data")).unwrap();
+        std::fs::write(
+            root.join("review_data").join("users.json"),
+            r#"[{"username":"alice","password_hash":"-","created":"2024-05-01T00:00:00Z","roles":["moderator"]}]"#,
+        ).unwrap();
+
+        let (status, body) = fetch(app(&root), "/reviewers").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, r#"["alice"]"#);
+
+        let assign = |index: usize, json: &'static str| {
+            let root = root.clone();
+            async move { send(app(&root), "PUT", &format!("/feedback/2024-05-01/entries/{index}/assignment"), json).await }
+        };
+        let (status, body) = assign(1, r#"{"assignee":"alice","due":"2024-05-03"}"#).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let (status, _) = assign(0, r#"{"assignee":"alice"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = assign(0, r#"{"assignee":"mallory"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = assign(0, r#"{"assignee":"alice","due":"soon"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = fetch(app_as(&root, "alice"), "/me/assignments").await;
+        assert_eq!(status, StatusCode::OK);
+        let assigned: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(assigned.as_array().unwrap().len(), 2);
+        assert_eq!(assigned[0]["entry"]["id"], "2024-05-01.1");
+        assert_eq!(assigned[0]["overdue"], true);
+        assert_eq!(assigned[1]["overdue"], false);
+
+        // Resolved entries are only listed on request
+        send(app(&root), "PUT", "/feedback/2024-05-01/entries/1/status", r#"{"status":"resolved"}"#).await;
+        let (_, body) = fetch(app_as(&root, "alice"), "/me/assignments").await;
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap().as_array().unwrap().len(), 1);
+        let (_, body) = fetch(app_as(&root, "alice"), "/me/assignments?done=true").await;
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&body).unwrap().as_array().unwrap().len(), 2);
+
+        let (status, body) = assign(0, r#"{"assignee":null}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(r#""assignment":null"#));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+use super::{check_entry, path_index, update_failed, Annotations, EntryIndex, Rejection};
+use crate::auth::Identity;
+use crate::days::DayIndex;
+use crate::storage::{DayDate, InvalidInput};
+use axum::extract::{Extension, FromRequestParts, State};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+const COMMENT_PARAM: &str = "comment";
+const MAX_COMMENT_LEN: usize = 10_000;
+
+/// A reviewer's note on an entry, part of the entry's thread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    /// Unique within the entry
+    pub id: usize,
+    pub author: String,
+    pub created: DateTime<Utc>,
+    /// Markdown, the frontend renders it
+    pub body: String,
+    /// Earlier bodies, oldest first
+    #[serde(default)]
+    pub history: Vec<Revision>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    pub body: String,
+    /// When this body was replaced by the next one
+    pub replaced: DateTime<Utc>,
+}
+
+/// The `{comment}` path segment
+#[derive(Debug, Clone, Copy)]
+pub struct CommentId(usize);
+
+#[derive(Debug, Deserialize)]
+pub struct CommentBody {
+    body: String,
+}
+
+impl<S> FromRequestParts<S> for CommentId
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        path_index(parts, state, COMMENT_PARAM, "invalid_comment").await.map(Self)
+    }
+}
+
+impl CommentBody {
+    fn validate(self) -> Result<String, InvalidInput> {
+        let body = self.body.trim();
+        if body.is_empty() {
+            return Err(InvalidInput::new("invalid_comment", "Comments can't be empty", ""));
+        }
+        if body.chars().count() > MAX_COMMENT_LEN {
+            return Err(InvalidInput::new(
+                "invalid_comment",
+                format!("Comments can't be longer than {MAX_COMMENT_LEN} characters"),
+                body.chars().count().to_string(),
+            ));
+        }
+        Ok(body.to_string())
+    }
+}
+
+pub async fn add_comment(
+    State(days): State<Arc<DayIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    Extension(identity): Extension<Identity>,
+    date: DayDate,
+    EntryIndex(index): EntryIndex,
+    Json(comment): Json<CommentBody>,
+) -> Response {
+    let body = match comment.validate() {
+        Ok(body) => body,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(response) = check_entry(&days, date, index).await {
+        return response;
+    }
+
+    let result = annotations.update(date, index, &identity.name, |annotation| {
+        let id = annotation.comments.last().map_or(0, |last| last.id + 1);
+        annotation.comments.push(Comment {
+            id,
+            author: identity.name.clone(),
+            created: Utc::now(),
+            body,
+            history: vec![],
+        });
+        Ok(())
+    }).await;
+
+    match result {
+        Ok(mut annotation) => {
+            let comment = annotation.comments.pop();
+            info!(%date, index, comment = comment.as_ref().map(|comment| comment.id), by = identity.name, "Added comment");
+            (StatusCode::CREATED, Json(comment)).into_response()
+        }
+        Err(e) => update_failed(e, date, index),
+    }
+}
+
+/// Only the author can edit a comment, the previous body is kept in its history
+pub async fn edit_comment(
+    State(annotations): State<Arc<Annotations>>,
+    Extension(identity): Extension<Identity>,
+    date: DayDate,
+    EntryIndex(index): EntryIndex,
+    CommentId(id): CommentId,
+    Json(comment): Json<CommentBody>,
+) -> Response {
+    let body = match comment.validate() {
+        Ok(body) => body,
+        Err(e) => return e.into_response(),
+    };
+
+    let result = annotations.update(date, index, &identity.name, |annotation| {
+        let comment = annotation.comments
+                                .iter_mut()
+                                .find(|comment| comment.id == id)
+                                .ok_or(Rejection::NotFound)?;
+        if comment.author != identity.name {
+            return Err(Rejection::Forbidden("not_author"));
+        }
+
+        let previous = std::mem::replace(&mut comment.body, body);
+        comment.history.push(Revision { body: previous, replaced: Utc::now() });
+        Ok(())
+    }).await;
+
+    match result {
+        Ok(annotation) => {
+            info!(%date, index, comment = id, by = identity.name, "Edited comment");
+            Json(annotation.comments.into_iter().find(|comment| comment.id == id)).into_response()
+        }
+        Err(e) => update_failed(e, date, index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{app, app_as, fetch, send, temp_root};
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn keeps_comment_threads() {
+        let root = temp_root("comments");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-05-01{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-01 - 12:00:00]z\nfirst\n{dashes}\n\n"),
+        ).unwrap();
+
+        let uri = "/feedback/2024-05-01/entries/0/comments";
+        let (status, body) = send(app(&root), "POST", uri, r#"{"body":"Looks like **#12**"}"#).await;
+        assert_eq!(status, StatusCode::CREATED, "{body}");
+        let comment: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(comment["id"], 0);
+        assert_eq!(comment["author"], "tester");
+
+        let (_, body) = send(app(&root), "POST", uri, r#"{"body":"Agreed"}"#).await;
+        let comment: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(comment["id"], 1);
+
+        let (status, body) = send(app(&root), "PUT", &format!("{uri}/0"), r#"{"body":"Duplicate of #12"}"#).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let comment: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(comment["body"], "Duplicate of #12");
+        assert_eq!(comment["history"][0]["body"], "Looks like **#12**");
+
+        let (status, _) = send(app_as(&root, "someone else"), "PUT", &format!("{uri}/0"), r#"{"body":"x"}"#).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send(app(&root), "PUT", &format!("{uri}/7"), r#"{"body":"x"}"#).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(app(&root), "POST", uri, r#"{"body":"  "}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, body) = fetch(app(&root), "/feedback/2024-05-01/annotations").await;
+        let annotations: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(annotations["2024-05-01.0"]["comments"].as_array().unwrap().len(), 2);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
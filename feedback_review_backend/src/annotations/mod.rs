@@ -0,0 +1,546 @@
+use crate::auth::Identity;
+use crate::days::DayIndex;
+use crate::storage::{DayDate, InvalidInput};
+use crate::store::JsonStore;
+use crate::{EntriesError, DATA_ROOT};
+use axum::extract::{Extension, FromRequestParts, Path, State};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::env;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
+use tokio::fs;
+use tokio::sync::Mutex;
+use std::fmt::{self, Display};
+use tracing::{debug, error, info, warn};
+
+mod assignments;
+mod comments;
+mod wall;
+
+pub use assignments::{my_assignments, set_assignment};
+use assignments::Assignment;
+pub use comments::{add_comment, edit_comment};
+use comments::Comment;
+pub use wall::set_wall;
+use wall::WallDecision;
+
+const ANNOTATION_DIR: &str = "annotations";
+const INDEX_PARAM: &str = "index";
+const MAX_TAG_LEN: usize = 32;
+const MAX_TAGS: usize = 20;
+/// Suggested in the frontend, reviewers can still add any other tag
+static PREDEFINED_TAGS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    const ENV_KEY: &str = "PREDEFINED_TAGS";
+    const DEFAULT_TAGS: &str = "bug,ux,praise";
+
+    let parse = |tags: &str| tags.split(',')
+                                 .filter(|tag| !tag.trim().is_empty())
+                                 .map(normalize_tag)
+                                 .collect::<Result<Vec<_>, _>>();
+
+    match env::var(ENV_KEY) {
+        Ok(tags) => {
+            parse(&tags).unwrap_or_else(|_| {
+                println!("WARNING: {ENV_KEY} is set, but the value is invalid, \
+                    using default ({DEFAULT_TAGS})");
+                parse(DEFAULT_TAGS).unwrap_or_default()
+            })
+        }
+        Err(_) => parse(DEFAULT_TAGS).unwrap_or_default(),
+    }
+});
+
+/// Where an entry is in the triage workflow, entries without an annotation are `New`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    #[default]
+    New,
+    Read,
+    InProgress,
+    Resolved,
+    Archived,
+}
+
+/// What reviewers recorded about an entry. The day files are only ever changed to delete
+/// entries, so this lives in [`DATA_ROOT`] beside them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Annotation {
+    #[serde(default)]
+    pub status: Status,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    /// Oldest first
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    pub assignment: Option<Assignment>,
+    /// Unset until a presenter approves or skips the entry for the feedback wall
+    pub wall: Option<WallDecision>,
+    pub updated: Option<Change>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    pub by: String,
+    pub at: DateTime<Utc>,
+}
+
+/// The annotations of a day by entry index
+type DayAnnotations = BTreeMap<usize, Annotation>;
+
+/// One JSON file per day, so a change only rewrites the annotations of that day
+pub struct Annotations {
+    root: PathBuf,
+    days: Mutex<HashMap<DayDate, Arc<JsonStore<DayAnnotations>>>>,
+}
+
+/// The `{index}` path segment of an entry
+#[derive(Debug, Clone, Copy)]
+pub struct EntryIndex(pub usize);
+
+/// Why a change was refused, nothing is saved then
+#[derive(Debug)]
+pub enum Rejection {
+    NotFound,
+    Forbidden(&'static str),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusUpdate {
+    status: Status,
+}
+
+/// Replaces every tag of the entry
+#[derive(Debug, Deserialize)]
+pub struct TagsUpdate {
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Tags {
+    predefined: &'static [String],
+    /// How many entries have each tag
+    used: BTreeMap<String, usize>,
+}
+
+/// Lowercase letters, digits, spaces, `-` and `_`, so `Bug` and `bug ` are the same tag
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err(String::from("Tags can't be empty"));
+    }
+    if tag.chars().count() > MAX_TAG_LEN {
+        return Err(format!("Tags can't be longer than {MAX_TAG_LEN} characters"));
+    }
+    if !tag.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_')) {
+        return Err(String::from("Tags may only contain letters, digits, spaces, - and _"));
+    }
+    Ok(tag)
+}
+
+impl Annotations {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), days: Mutex::new(HashMap::new()) }
+    }
+
+    async fn store(&self, date: DayDate) -> Arc<JsonStore<DayAnnotations>> {
+        self.days
+            .lock()
+            .await
+            .entry(date)
+            .or_insert_with(|| Arc::new(JsonStore::at(self.root.join(format!("{date}.json")))))
+            .clone()
+    }
+
+    /// Keyed by entry id (`{date}.{index}`), entries without an annotation are left out
+    pub async fn day(&self, date: DayDate) -> BTreeMap<String, Annotation> {
+        self.store(date)
+            .await
+            .read(|annotations| {
+                annotations.iter()
+                           .map(|(index, annotation)| (format!("{date}.{index}"), annotation.clone()))
+                           .collect()
+            })
+            .await
+    }
+
+    /// Every day with a stored annotation file
+    async fn stored_days(&self) -> io::Result<Vec<DayDate>> {
+        let mut dir = match fs::read_dir(&self.root).await {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+
+        let mut days = vec![];
+        while let Some(file) = dir.next_entry().await? {
+            let name = file.file_name();
+            if let Some(date) = name.to_str()
+                                    .and_then(|name| name.strip_suffix(".json"))
+                                    .and_then(|date| date.parse().ok()) {
+                days.push(date);
+            }
+        }
+        Ok(days)
+    }
+
+    /// `(date, index)` of every entry with `tag`
+    pub async fn tagged(&self, tag: &str) -> io::Result<HashSet<(DayDate, usize)>> {
+        let mut tagged = HashSet::new();
+        for date in self.stored_days().await? {
+            self.store(date)
+                .await
+                .read(|annotations| {
+                    tagged.extend(annotations.iter()
+                                             .filter(|(_, annotation)| annotation.tags.contains(tag))
+                                             .map(|(index, _)| (date, *index)));
+                })
+                .await;
+        }
+        Ok(tagged)
+    }
+
+    /// `(date, index, annotation)` of every entry assigned to `name`
+    pub async fn assigned_to(&self, name: &str) -> io::Result<Vec<(DayDate, usize, Annotation)>> {
+        let mut assigned = vec![];
+        for date in self.stored_days().await? {
+            self.store(date)
+                .await
+                .read(|annotations| {
+                    assigned.extend(annotations.iter()
+                                               .filter(|(_, annotation)| {
+                                                   annotation.assignment
+                                                             .as_ref()
+                                                             .is_some_and(|assignment| assignment.assignee == name)
+                                               })
+                                               .map(|(index, annotation)| (date, *index, annotation.clone())));
+                })
+                .await;
+        }
+        Ok(assigned)
+    }
+
+    async fn tag_counts(&self) -> io::Result<BTreeMap<String, usize>> {
+        let mut counts = BTreeMap::new();
+        for date in self.stored_days().await? {
+            self.store(date)
+                .await
+                .read(|annotations| {
+                    for tag in annotations.values().flat_map(|annotation| &annotation.tags) {
+                        *counts.entry(tag.clone()).or_default() += 1;
+                    }
+                })
+                .await;
+        }
+        Ok(counts)
+    }
+
+    /// Runs `f` on the annotation of the entry and records who changed it.
+    /// If `f` returns a [`Rejection`], it is the error and nothing is saved.
+    pub async fn update(
+        &self,
+        date: DayDate,
+        index: usize,
+        by: &str,
+        f: impl FnOnce(&mut Annotation) -> Result<(), Rejection>,
+    ) -> anyhow::Result<Annotation> {
+        self.store(date)
+            .await
+            .update(|annotations| {
+                let existed = annotations.contains_key(&index);
+                let annotation = annotations.entry(index).or_default();
+                if let Err(rejection) = f(annotation) {
+                    if !existed {
+                        annotations.remove(&index);
+                    }
+                    return Err(rejection.into());
+                }
+                annotation.updated = Some(Change { by: by.to_string(), at: Utc::now() });
+                Ok(annotation.clone())
+            })
+            .await
+    }
+
+    /// Drops everything recorded about the entry, comments may quote what was deleted
+    pub async fn remove(&self, date: DayDate, index: usize) -> anyhow::Result<Option<Annotation>> {
+        self.store(date)
+            .await
+            .update(|annotations| Ok(annotations.remove(&index)))
+            .await
+    }
+}
+
+impl Default for Annotations {
+    fn default() -> Self {
+        Self::new(PathBuf::from(DATA_ROOT).join(ANNOTATION_DIR))
+    }
+}
+
+impl Status {
+    /// Nothing left to follow up on
+    pub fn is_done(self) -> bool {
+        matches!(self, Self::Resolved | Self::Archived)
+    }
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "not found"),
+            Self::Forbidden(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+impl<S> FromRequestParts<S> for EntryIndex
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        path_index(parts, state, INDEX_PARAM, "invalid_index").await.map(Self)
+    }
+}
+
+/// A numeric path segment, rejected with 400 if it isn't one
+async fn path_index<S: Send + Sync>(
+    parts: &mut Parts,
+    state: &S,
+    param: &str,
+    error: &'static str,
+) -> Result<usize, Response> {
+    let Path(params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    let value = params.get(param).cloned().unwrap_or_default();
+    value.parse().map_err(|e| {
+        warn!(value, param, "Rejected invalid path segment");
+        InvalidInput::new(error, format!("Invalid {param}: {e}"), value).into_response()
+    })
+}
+
+/// 404 and 403 for a [`Rejection`], 500 for everything else
+fn update_failed(e: anyhow::Error, date: DayDate, index: usize) -> Response {
+    match e.downcast_ref::<Rejection>() {
+        Some(Rejection::NotFound) => (StatusCode::NOT_FOUND, Json(EntriesError { error: "not_found" })).into_response(),
+        Some(Rejection::Forbidden(reason)) => (StatusCode::FORBIDDEN, Json(EntriesError { error: reason })).into_response(),
+        None => {
+            error!("Failed to save the annotation of {date}.{index}: {e:#}");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "write_failed" })).into_response()
+        }
+    }
+}
+
+/// Annotations are only accepted for entries that exist
+async fn check_entry(days: &DayIndex, date: DayDate, index: usize) -> Result<(), Response> {
+    match days.day(date).await {
+        Ok(Some(day)) if day.entries.iter().any(|entry| entry.index == index) => Ok(()),
+        Ok(_) => Err((StatusCode::NOT_FOUND, Json(EntriesError { error: "not_found" })).into_response()),
+        Err(e) => {
+            error!("Failed to read feedback for date {date}: {e}");
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response())
+        }
+    }
+}
+
+/// For `?tag=` on the listing endpoints, `None` if there is no tag to filter by
+pub async fn tag_filter(annotations: &Annotations, tag: Option<&str>) -> Result<Option<HashSet<(DayDate, usize)>>, Response> {
+    let Some(tag) = tag else {
+        return Ok(None);
+    };
+    let tag = normalize_tag(tag).map_err(|message| InvalidInput::new("invalid_tag", message, tag).into_response())?;
+
+    match annotations.tagged(&tag).await {
+        Ok(tagged) => Ok(Some(tagged)),
+        Err(e) => {
+            error!("Failed to read annotations: {e}");
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response())
+        }
+    }
+}
+
+pub async fn get_tags(State(annotations): State<Arc<Annotations>>) -> Response {
+    match annotations.tag_counts().await {
+        Ok(used) => Json(Tags { predefined: &PREDEFINED_TAGS, used }).into_response(),
+        Err(e) => {
+            error!("Failed to read annotations: {e}");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response()
+        }
+    }
+}
+
+pub async fn get_annotations(State(annotations): State<Arc<Annotations>>, date: DayDate) -> impl IntoResponse {
+    debug!(%date, "Getting annotations");
+    Json(annotations.day(date).await)
+}
+
+pub async fn set_status(
+    State(days): State<Arc<DayIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    Extension(identity): Extension<Identity>,
+    date: DayDate,
+    EntryIndex(index): EntryIndex,
+    Json(update): Json<StatusUpdate>,
+) -> Response {
+    if let Err(response) = check_entry(&days, date, index).await {
+        return response;
+    }
+
+    let result = annotations.update(date, index, &identity.name, |annotation| {
+        annotation.status = update.status;
+        Ok(())
+    }).await;
+
+    match result {
+        Ok(annotation) => {
+            info!(%date, index, status = ?update.status, by = identity.name, "Changed status");
+            Json(annotation).into_response()
+        }
+        Err(e) => update_failed(e, date, index),
+    }
+}
+
+pub async fn set_tags(
+    State(days): State<Arc<DayIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    Extension(identity): Extension<Identity>,
+    date: DayDate,
+    EntryIndex(index): EntryIndex,
+    Json(update): Json<TagsUpdate>,
+) -> Response {
+    let tags = update.tags
+                     .iter()
+                     .map(|tag| normalize_tag(tag).map_err(|message| (message, tag)))
+                     .collect::<Result<BTreeSet<_>, _>>();
+    let tags = match tags {
+        Ok(tags) => tags,
+        Err((message, tag)) => return InvalidInput::new("invalid_tag", message, tag.as_str()).into_response(),
+    };
+    if tags.len() > MAX_TAGS {
+        return InvalidInput::new("invalid_tag", format!("At most {MAX_TAGS} tags per entry"), tags.len().to_string())
+            .into_response();
+    }
+
+    if let Err(response) = check_entry(&days, date, index).await {
+        return response;
+    }
+
+    let result = annotations.update(date, index, &identity.name, |annotation| {
+        annotation.tags = tags;
+        Ok(())
+    }).await;
+
+    match result {
+        Ok(annotation) => {
+            info!(%date, index, tags = ?annotation.tags, by = identity.name, "Changed tags");
+            Json(annotation).into_response()
+        }
+        Err(e) => update_failed(e, date, index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{app, fetch, send, temp_root};
+    use axum::http::StatusCode;
+
+    #[test]
+    fn normalizes_tags() {
+        assert_eq!(normalize_tag(" Bug ").unwrap(), "bug");
+        assert_eq!(normalize_tag("needs-info").unwrap(), "needs-info");
+        assert_eq!(normalize_tag("ux_flow 2").unwrap(), "ux_flow 2");
+
+        for tag in ["", "   ", "a/b", "<script>", &"x".repeat(MAX_TAG_LEN + 1)] {
+            assert!(normalize_tag(tag).is_err(), "{tag:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn changes_entry_status() {
+        let root = temp_root("status");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-05-01{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-01 - 12:00:00]z\nfirst\n{dashes}\n\n"),
+        ).unwrap();
+
+        let (status, body) = fetch(app(&root), "/feedback/2024-05-01/annotations").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "{}");
+
+        let uri = "/feedback/2024-05-01/entries/0/status";
+        let (status, body) = send(app(&root), "PUT", uri, r#"{"status":"in_progress"}"#).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+
+        let (_, body) = fetch(app(&root), "/feedback/2024-05-01/annotations").await;
+        let annotations: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(annotations["2024-05-01.0"]["status"], "in_progress");
+        assert_eq!(annotations["2024-05-01.0"]["updated"]["by"], "tester");
+
+        let (status, _) = send(app(&root), "PUT", "/feedback/2024-05-01/entries/1/status", r#"{"status":"read"}"#).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(app(&root), "PUT", "/feedback/2024-05-01/entries/x/status", r#"{"status":"read"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(app(&root), "PUT", uri, r#"{"status":"done"}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn filters_by_tag() {
+        let root = temp_root("tags");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-05-01{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-01 - 12:00:00]z\nlogin broken\n{dashes}\n\n{dashes}\n[2024-05-01 - 13:00:00]z\nlogin great\n{dashes}\n\n"),
+        ).unwrap();
+        std::fs::write(
+            root.join(format!("2024-05-02{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-02 - 12:00:00]z\nlogin slow\n{dashes}\n\n"),
+        ).unwrap();
+
+        let uri = "/feedback/2024-05-01/entries/1/tags";
+        let (status, body) = send(app(&root), "PUT", uri, r#"{"tags":["Praise ","ux","praise"]}"#).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let annotation: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(annotation["tags"], serde_json::json!(["praise", "ux"]));
+
+        let (status, _) = send(app(&root), "PUT", uri, r#"{"tags":["a/b"]}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, body) = fetch(app(&root), "/dates?tag=praise").await;
+        let dates: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(dates["dates"].as_array().unwrap().len(), 1);
+        assert_eq!(dates["dates"][0]["count"], 1);
+
+        let (_, body) = fetch(app(&root), "/feedback/2024-05-01/entries?tag=PRAISE").await;
+        let entries: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(entries.as_array().unwrap().len(), 1);
+        assert_eq!(entries[0]["id"], "2024-05-01.1");
+
+        let (_, body) = fetch(app(&root), "/search?q=login&tag=ux").await;
+        let results: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(results["total"], 1);
+
+        let (_, body) = fetch(app(&root), "/tags").await;
+        let tags: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(tags["used"], serde_json::json!({ "praise": 1, "ux": 1 }));
+        assert_eq!(tags["predefined"], serde_json::json!(["bug", "ux", "praise"]));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
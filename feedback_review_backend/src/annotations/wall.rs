@@ -0,0 +1,116 @@
+use super::{check_entry, update_failed, Annotations, EntryIndex};
+use crate::auth::Identity;
+use crate::days::DayIndex;
+use crate::storage::DayDate;
+use axum::extract::{Extension, State};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// What a presenter decided for the feedback wall, entries without a decision wait in the queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Approval {
+    /// Shown on the wall
+    Approved,
+    /// Left out, or taken off the wall again
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WallDecision {
+    pub approval: Approval,
+    pub by: String,
+    pub at: DateTime<Utc>,
+}
+
+/// `approval: null` puts the entry back into the queue
+#[derive(Debug, Deserialize)]
+pub struct WallUpdate {
+    approval: Option<Approval>,
+}
+
+pub async fn set_wall(
+    State(days): State<Arc<DayIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    Extension(identity): Extension<Identity>,
+    date: DayDate,
+    EntryIndex(index): EntryIndex,
+    Json(update): Json<WallUpdate>,
+) -> Response {
+    if let Err(response) = check_entry(&days, date, index).await {
+        return response;
+    }
+
+    let result = annotations.update(date, index, &identity.name, |annotation| {
+        annotation.wall = update.approval.map(|approval| WallDecision {
+            approval,
+            by: identity.name.clone(),
+            at: Utc::now(),
+        });
+        Ok(())
+    }).await;
+
+    match result {
+        Ok(annotation) => {
+            info!(%date, index, approval = ?update.approval, by = identity.name, "Changed wall approval");
+            Json(annotation).into_response()
+        }
+        Err(e) => update_failed(e, date, index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{add_token, anonymous_app, app, fetch, send, status_of, temp_root, token};
+    use axum::http::{header, StatusCode};
+
+    #[tokio::test]
+    async fn keeps_wall_approvals_apart_from_tags() {
+        let root = temp_root("wall");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-05-01{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-01 - 12:00:00]z\nfirst\n{dashes}\n\n{dashes}\n[2024-05-01 - 13:00:00]z\nsecond\n{dashes}\n\n"),
+        ).unwrap();
+
+        let uri = |index: usize| format!("/feedback/2024-05-01/entries/{index}/wall");
+        let (status, body) = send(app(&root), "PUT", &uri(0), r#"{"approval":"approved"}"#).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let annotation: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(annotation["wall"]["approval"], "approved");
+        assert_eq!(annotation["wall"]["by"], "tester");
+        assert_eq!(annotation["tags"], serde_json::json!([]));
+
+        let (status, _) = send(app(&root), "PUT", &uri(1), r#"{"approval":"skipped"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(app(&root), "PUT", &uri(1), r#"{"approval":"maybe"}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, _) = send(app(&root), "PUT", &uri(2), r#"{"approval":"approved"}"#).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // Skipping is remembered across reloads, approving is not a tag
+        let (_, body) = fetch(app(&root), "/feedback/2024-05-01/annotations").await;
+        let annotations: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(annotations["2024-05-01.1"]["wall"]["approval"], "skipped");
+        let (_, body) = fetch(app(&root), "/tags").await;
+        let tags: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(tags["used"], serde_json::json!({}));
+
+        let (status, body) = send(app(&root), "PUT", &uri(1), r#"{"approval":null}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        let annotation: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(annotation["wall"], serde_json::Value::Null);
+
+        // Moderating doesn't include the wall
+        add_token(&root, "moderator", &["moderator"]);
+        let moderator = Some((header::AUTHORIZATION, format!("Bearer {}", token("moderator"))));
+        assert_eq!(status_of(anonymous_app(&root), "PUT", &uri(0), moderator).await, StatusCode::FORBIDDEN);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
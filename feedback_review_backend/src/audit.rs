@@ -0,0 +1,152 @@
+use crate::DATA_ROOT;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+const AUDIT_FILE: &str = "audit.log";
+
+/// What was done, new actions are only ever added
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    DeleteEntry,
+}
+
+/// One line of the audit log: who did what to which target, when and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub at: DateTime<Utc>,
+    pub actor: String,
+    pub action: Action,
+    /// e.g. the entry id `{date}.{index}`
+    pub target: String,
+    pub reason: String,
+}
+
+/// Append-only, one JSON object per line. Nothing in the backend rewrites or truncates it,
+/// a [rotating](Self::rotating) log only drops its oldest file.
+pub struct AuditLog {
+    path: PathBuf,
+    /// Once the file reached this size, it is moved to `<path>.1`, replacing the one before
+    rotate_at: Option<u64>,
+    write: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), rotate_at: None, write: Mutex::new(()) }
+    }
+
+    /// Keeps at most about twice `max_bytes` on disk, older events are dropped
+    pub fn rotating(path: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self { rotate_at: Some(max_bytes), ..Self::new(path) }
+    }
+
+    fn rotated(&self) -> PathBuf {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        rotated.into()
+    }
+
+    /// Only returns once the event is on disk
+    pub async fn record(&self, event: &impl Serialize) -> io::Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+
+        let _write = self.write.lock().await;
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+
+        if let Some(max_bytes) = self.rotate_at
+            && file.metadata().await?.len() >= max_bytes
+        {
+            fs::rename(&self.path, self.rotated()).await?;
+        }
+        Ok(())
+    }
+
+    /// Every event, oldest first, one at a time so the log is never read into memory at once.
+    /// Lines that don't parse are skipped.
+    pub async fn visit<T: DeserializeOwned>(&self, mut visit: impl FnMut(T)) -> io::Result<()> {
+        for path in [self.rotated(), self.path.clone()] {
+            let file = match File::open(&path).await {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+
+            let mut lines = BufReader::new(file).lines();
+            while let Some(line) = lines.next_line().await? {
+                if line.is_empty() {
+                    continue;
+                }
+                match serde_json::from_str(&line) {
+                    Ok(event) => visit(event),
+                    Err(e) => warn!("Skipping invalid line in {}: {e}", path.display()),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new(PathBuf::from(DATA_ROOT).join(AUDIT_FILE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_root;
+
+    async fn visited(log: &AuditLog) -> Vec<u32> {
+        let mut events = vec![];
+        log.visit(|event: u32| events.push(event)).await.unwrap();
+        events
+    }
+
+    #[tokio::test]
+    async fn rotates_at_the_size_limit() {
+        let root = temp_root("audit_rotation");
+        let path = root.join("access.log");
+        // Every event is two bytes with its newline
+        let log = AuditLog::rotating(&path, 6);
+
+        for event in 1..=2 {
+            log.record(&event).await.unwrap();
+        }
+        assert_eq!(visited(&log).await, [1, 2]);
+
+        log.record(&3).await.unwrap();
+        assert!(!path.exists());
+        log.record(&4).await.unwrap();
+        assert_eq!(visited(&log).await, [1, 2, 3, 4]);
+
+        // The second rotation drops the oldest file
+        for event in 5..=7 {
+            log.record(&event).await.unwrap();
+        }
+        assert_eq!(visited(&log).await, [4, 5, 6, 7]);
+
+        std::fs::write(&path, "7\nnot json\n8\n").unwrap();
+        assert_eq!(visited(&log).await, [4, 5, 6, 7, 8]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
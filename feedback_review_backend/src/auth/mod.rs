@@ -0,0 +1,454 @@
+mod roles;
+mod sessions;
+mod tokens;
+mod users;
+
+use crate::auth::roles::Role;
+use crate::auth::sessions::{SessionStore, SESSION_TTL};
+use crate::auth::tokens::TokenStore;
+use crate::auth::users::UserStore;
+use anyhow::{bail, Context, Result};
+use axum::extract::{Extension, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::MethodRouter;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+
+pub use crate::auth::roles::Permission;
+
+const SESSION_COOKIE: &str = "review_session";
+const USAGE: &str = "Usage: feedback_review_backend \
+    <token <add NAME [ROLE...] | list | revoke NAME> \
+    | user <add NAME [ROLE...] | roles NAME ROLE... | passwd NAME | list | logout NAME | remove NAME>>\n\
+    Roles are viewer (default), presenter, moderator and admin";
+
+/// Who made the request, inserted into the request extensions by [`require_auth`]
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub name: String,
+    pub roles: Vec<Role>,
+}
+
+/// Static tokens for scripts, reviewer accounts with sessions for the frontend
+pub struct Auth {
+    tokens: TokenStore,
+    users: UserStore,
+    sessions: SessionStore,
+}
+
+#[derive(Debug, Serialize)]
+struct AuthError {
+    error: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Credentials {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Serialize)]
+struct LoginResponse {
+    name: String,
+    expires: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct Me {
+    name: String,
+    roles: Vec<Role>,
+    permissions: Vec<Permission>,
+    /// The server's clock, the frontend uses it to agree with the backend on which day is today
+    now: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct Forbidden {
+    error: &'static str,
+    permission: Permission,
+}
+
+impl Auth {
+    pub fn new() -> Self {
+        Self {
+            tokens: TokenStore::new(),
+            users: UserStore::new(),
+            sessions: SessionStore::new(),
+        }
+    }
+
+    /// Keeps the stores in `dir` instead of [`DATA_ROOT`](crate::DATA_ROOT)
+    pub fn in_dir(dir: &Path) -> Self {
+        Self {
+            tokens: TokenStore::in_dir(dir),
+            users: UserStore::in_dir(dir),
+            sessions: SessionStore::in_dir(dir),
+        }
+    }
+
+    /// Reviewer accounts, tokens are for scripts and can't be assigned anything
+    pub async fn reviewers(&self) -> Vec<String> {
+        self.users
+            .list()
+            .await
+            .into_iter()
+            .map(|(name, _, _)| name)
+            .collect()
+    }
+
+    pub async fn is_reviewer(&self, name: &str) -> bool {
+        self.users.roles(name).await.is_some()
+    }
+
+    async fn identify(&self, headers: &HeaderMap) -> Option<Identity> {
+        if let Some(token) = bearer(headers) {
+            return self.tokens.verify(token).await;
+        }
+        if let Some(session) = session_cookie(headers) {
+            let name = self.sessions.verify(session).await?;
+            let roles = self.users.roles(&name).await?;
+            return Some(Identity { name, roles });
+        }
+        None
+    }
+}
+
+impl Identity {
+    pub fn can(&self, permission: Permission) -> bool {
+        self.roles
+            .iter()
+            .any(|role| role.permissions().contains(&permission))
+    }
+}
+
+/// Accepts either `Authorization: Bearer <token>` or the session cookie set by [`login`]
+pub async fn require_auth(
+    State(auth): State<Arc<Auth>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(identity) = auth.identify(req.headers()).await else {
+        warn!("Rejected unauthenticated request");
+        return unauthorized("Missing or invalid token or session");
+    };
+
+    debug!(identity.name, "Authenticated");
+    req.extensions_mut().insert(identity);
+    next.run(req).await
+}
+
+/// Declares the permission a route needs, checked after [`require_auth`] identified the caller
+pub fn require<S>(permission: Permission, method_router: MethodRouter<S>) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    method_router.route_layer(middleware::from_fn_with_state(permission, check_permission))
+}
+
+async fn check_permission(
+    State(permission): State<Permission>,
+    Extension(identity): Extension<Identity>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !identity.can(permission) {
+        warn!(identity.name, ?permission, "Rejected request without permission");
+        return (
+            StatusCode::FORBIDDEN,
+            Json(Forbidden { error: "Missing permission", permission }),
+        ).into_response();
+    }
+
+    next.run(req).await
+}
+
+pub async fn login(State(auth): State<Arc<Auth>>, Json(credentials): Json<Credentials>) -> Response {
+    if !auth.users.verify(&credentials.username, &credentials.password).await {
+        warn!(credentials.username, "Failed login");
+        return unauthorized("Invalid username or password");
+    }
+
+    let (session, expires) = match auth.sessions.create(&credentials.username).await {
+        Ok(session) => session,
+        Err(e) => {
+            error!("Failed to create session for {}: {e:#}", credentials.username);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(AuthError { error: "Failed to create session" }))
+                .into_response();
+        }
+    };
+
+    info!(credentials.username, "Logged in");
+
+    // The frontend may be served from another origin, but has to be on the same site
+    // (e.g. review.example.com and api.example.com), `SameSite=Lax` keeps the cookie out of cross site requests
+    let cookie = format!(
+        "{SESSION_COOKIE}={session}; Path=/; HttpOnly; Secure; SameSite=Lax; Max-Age={}",
+        SESSION_TTL.num_seconds(),
+    );
+
+    (
+        StatusCode::OK,
+        [(header::SET_COOKIE, cookie)],
+        Json(LoginResponse { name: credentials.username, expires }),
+    ).into_response()
+}
+
+pub async fn logout(State(auth): State<Arc<Auth>>, headers: HeaderMap) -> Response {
+    if let Some(session) = session_cookie(&headers)
+        && let Err(e) = auth.sessions.revoke(session).await {
+        error!("Failed to revoke session: {e:#}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(AuthError { error: "Failed to revoke session" }))
+            .into_response();
+    }
+
+    let cookie = format!("{SESSION_COOKIE}=; Path=/; HttpOnly; Secure; SameSite=Lax; Max-Age=0");
+
+    (StatusCode::NO_CONTENT, [(header::SET_COOKIE, cookie)]).into_response()
+}
+
+pub async fn reviewers(State(auth): State<Arc<Auth>>) -> impl IntoResponse {
+    Json(auth.reviewers().await)
+}
+
+pub async fn me(Extension(identity): Extension<Identity>) -> impl IntoResponse {
+    Json(Me {
+        permissions: roles::permissions(&identity.roles),
+        name: identity.name,
+        roles: identity.roles,
+        now: Utc::now(),
+    })
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers.get(header::AUTHORIZATION)?
+           .to_str()
+           .ok()?
+           .strip_prefix("Bearer ")
+           .map(str::trim)
+           .filter(|token| !token.is_empty())
+}
+
+fn session_cookie(headers: &HeaderMap) -> Option<&str> {
+    headers.get_all(header::COOKIE)
+           .iter()
+           .filter_map(|cookies| cookies.to_str().ok())
+           .flat_map(|cookies| cookies.split(';'))
+           .filter_map(|cookie| cookie.trim().split_once('='))
+           .find(|(name, _)| *name == SESSION_COOKIE)
+           .map(|(_, value)| value)
+           .filter(|value| !value.is_empty())
+}
+
+fn unauthorized(error: &'static str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        Json(AuthError { error }),
+    ).into_response()
+}
+
+fn sha256_hex(value: &str) -> String {
+    Sha256::digest(value.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn random_hex() -> String {
+    rand::random::<[u8; 32]>()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn read_password() -> Result<String> {
+    print!("Password: ");
+    io::stdout().flush().context("Failed to flush stdout")?;
+
+    let mut password = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut password)
+        .context("Failed to read password")?;
+
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// `feedback_review_backend token ...` and `feedback_review_backend user ...`,
+/// run them inside the container so they use the same volume
+pub async fn cli(args: &[String]) -> Result<()> {
+    let auth = Auth::new();
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["token", "add", name, ref roles @ ..] => {
+            let roles = Role::parse_all(roles)?;
+            let token = auth.tokens.add(name, roles).await?;
+            println!("Created token {name}, it will not be shown again:\n{token}");
+        }
+        ["token", "list"] => {
+            for (name, created, roles) in auth.tokens.list().await {
+                println!("{name}\t{}\t{}", created.to_rfc3339(), Role::join(&roles));
+            }
+        }
+        ["token", "revoke", name] => {
+            auth.tokens.revoke(name).await?;
+            println!("Revoked token {name}");
+        }
+        ["user", "add", name, ref roles @ ..] => {
+            let roles = Role::parse_all(roles)?;
+            auth.users.add(name, &read_password()?, roles).await?;
+            println!("Created user {name}");
+        }
+        ["user", "roles", name, ref roles @ ..] if !roles.is_empty() => {
+            let roles = Role::parse_all(roles)?;
+            let joined = Role::join(&roles);
+            auth.users.set_roles(name, roles).await?;
+            println!("{name} is now {joined}");
+        }
+        ["user", "passwd", name] => {
+            auth.users.set_password(name, &read_password()?).await?;
+            let revoked = auth.sessions.revoke_user(name).await?;
+            println!("Changed password of {name} and revoked {revoked} sessions");
+        }
+        ["user", "list"] => {
+            for (name, created, roles) in auth.users.list().await {
+                println!("{name}\t{}\t{}", created.to_rfc3339(), Role::join(&roles));
+            }
+        }
+        ["user", "logout", name] => {
+            let revoked = auth.sessions.revoke_user(name).await?;
+            println!("Revoked {revoked} sessions of {name}");
+        }
+        ["user", "remove", name] => {
+            auth.users.remove(name).await?;
+            let revoked = auth.sessions.revoke_user(name).await?;
+            println!("Removed user {name} and revoked {revoked} sessions");
+        }
+        _ => bail!(USAGE),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{add_token, anonymous_app, status_of, temp_root, token};
+    use argon2::password_hash::SaltString;
+    use argon2::{Argon2, PasswordHasher};
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn enforces_permissions_per_role() {
+        let root = temp_root("permissions");
+        let roles = ["viewer", "presenter", "moderator", "admin"];
+        for role in roles {
+            add_token(&root, role, &[role]);
+        }
+
+        let entry = "/feedback/2024-05-01/entries/0";
+        // Which of viewer, presenter, moderator and admin may use the route
+        let read = [true, true, true, true];
+        let present = [false, true, false, true];
+        let moderate = [false, false, true, true];
+        let export = [false, false, true, true];
+        let admin = [false, false, false, true];
+        let routes = [
+            ("GET", "/dates".to_string(), read),
+            ("GET", "/feedback?from=2024-05-01".to_string(), read),
+            ("GET", "/feedback/2024-05-01".to_string(), read),
+            ("GET", "/feedback/2024-05-01/entries".to_string(), read),
+            ("DELETE", entry.to_string(), admin),
+            ("GET", "/feedback/2024-05-01/annotations".to_string(), read),
+            ("PUT", format!("{entry}/status"), moderate),
+            ("PUT", format!("{entry}/tags"), moderate),
+            ("POST", format!("{entry}/comments"), moderate),
+            ("PUT", format!("{entry}/comments/0"), moderate),
+            ("PUT", format!("{entry}/assignment"), moderate),
+            ("PUT", format!("{entry}/wall"), present),
+            ("GET", "/tags".to_string(), read),
+            ("GET", "/reviewers".to_string(), moderate),
+            ("GET", "/me/assignments".to_string(), read),
+            ("GET", "/events".to_string(), read),
+            ("GET", "/search?q=x".to_string(), read),
+            ("GET", "/stats".to_string(), read),
+            ("GET", "/export".to_string(), export),
+            ("GET", "/audit/access".to_string(), admin),
+            ("GET", "/me".to_string(), read),
+        ];
+
+        for (method, uri, allowed) in routes {
+            let status = status_of(anonymous_app(&root), method, &uri, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "anonymous {method} {uri}");
+            let invalid = Some((header::AUTHORIZATION, format!("Bearer {}", token("nobody"))));
+            let status = status_of(anonymous_app(&root), method, &uri, invalid).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "invalid token {method} {uri}");
+
+            for (role, allowed) in roles.into_iter().zip(allowed) {
+                let credentials = Some((header::AUTHORIZATION, format!("Bearer {}", token(role))));
+                let status = status_of(anonymous_app(&root), method, &uri, credentials).await;
+                if allowed {
+                    assert!(![StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN].contains(&status), "{role} {method} {uri}: {status}");
+                } else {
+                    assert_eq!(status, StatusCode::FORBIDDEN, "{role} {method} {uri}");
+                }
+            }
+        }
+
+        // Public routes
+        for uri in ["/version", "/healthz"] {
+            assert_eq!(status_of(anonymous_app(&root), "GET", uri, None).await, StatusCode::OK, "{uri}");
+        }
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn logs_in_with_sessions() {
+        let root = temp_root("sessions");
+        std::fs::create_dir_all(root.join("review_data")).unwrap();
+        let salt = SaltString::encode_b64(&[7; 16]).unwrap();
+        let password_hash = Argon2::default().hash_password(b"correct horse", &salt).unwrap().to_string();
+        std::fs::write(
+            root.join("review_data").join("users.json"),
+            serde_json::json!([{ "username": "alice", "password_hash": password_hash, "created": "2024-05-01T00:00:00Z", "roles": ["viewer"] }]).to_string(),
+        ).unwrap();
+
+        let login = |password: &'static str| {
+            let app = anonymous_app(&root);
+            async move {
+                let json = serde_json::json!({ "username": "alice", "password": password }).to_string();
+                let req = Request::post("/login")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(json))
+                    .unwrap();
+                app.oneshot(req).await.unwrap()
+            }
+        };
+        assert_eq!(login("wrong horse").await.status(), StatusCode::UNAUTHORIZED);
+
+        let res = login("correct horse").await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let cookie = res.headers()[header::SET_COOKIE].to_str().unwrap().to_string();
+        assert!(cookie.contains("HttpOnly; Secure; SameSite=Lax"), "{cookie}");
+        let session = Some((header::COOKIE, cookie.split(';').next().unwrap().to_string()));
+
+        assert_eq!(status_of(anonymous_app(&root), "GET", "/me", session.clone()).await, StatusCode::OK);
+        assert_eq!(status_of(anonymous_app(&root), "GET", "/dates", session.clone()).await, StatusCode::OK);
+        assert_eq!(status_of(anonymous_app(&root), "GET", "/export", session.clone()).await, StatusCode::FORBIDDEN);
+
+        assert_eq!(status_of(anonymous_app(&root), "POST", "/logout", session.clone()).await, StatusCode::NO_CONTENT);
+        assert_eq!(status_of(anonymous_app(&root), "GET", "/me", session).await, StatusCode::UNAUTHORIZED);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
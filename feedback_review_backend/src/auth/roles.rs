@@ -0,0 +1,138 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    /// Runs the feedback wall in live sessions
+    Presenter,
+    Moderator,
+    Admin,
+}
+
+/// What a route needs, every route behind the login declares one with [`super::require`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    /// Read feedback and everything derived from it
+    Read,
+    /// Triage, tag, comment on and assign feedback
+    Moderate,
+    /// Download feedback in bulk
+    Export,
+    /// Delete feedback for good
+    Delete,
+    /// Browse who accessed what
+    Audit,
+    /// Approve entries for the feedback wall
+    Present,
+}
+
+impl Role {
+    pub fn permissions(self) -> &'static [Permission] {
+        match self {
+            Self::Viewer => &[Permission::Read],
+            Self::Presenter => &[Permission::Read, Permission::Present],
+            Self::Moderator => &[Permission::Read, Permission::Moderate, Permission::Export],
+            Self::Admin => &[
+                Permission::Read,
+                Permission::Moderate,
+                Permission::Export,
+                Permission::Delete,
+                Permission::Audit,
+                Permission::Present,
+            ],
+        }
+    }
+
+    /// Used if a user or token was created without naming roles
+    pub fn default_roles() -> Vec<Role> {
+        vec![Role::Viewer]
+    }
+
+    /// Parses the roles given on the command line, without any the defaults are used
+    pub fn parse_all(roles: &[&str]) -> Result<Vec<Role>> {
+        if roles.is_empty() {
+            return Ok(Self::default_roles());
+        }
+
+        let mut roles = roles.iter()
+                             .map(|role| role.parse())
+                             .collect::<Result<Vec<_>>>()?;
+        roles.sort();
+        roles.dedup();
+        Ok(roles)
+    }
+
+    pub fn join(roles: &[Role]) -> String {
+        roles.iter()
+             .map(ToString::to_string)
+             .collect::<Vec<_>>()
+             .join(",")
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(role: &str) -> Result<Self> {
+        match role {
+            "viewer" => Ok(Self::Viewer),
+            "presenter" => Ok(Self::Presenter),
+            "moderator" => Ok(Self::Moderator),
+            "admin" => Ok(Self::Admin),
+            _ => bail!("Unknown role {role}, expected one of viewer, presenter, moderator or admin"),
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Viewer => write!(f, "viewer"),
+            Self::Presenter => write!(f, "presenter"),
+            Self::Moderator => write!(f, "moderator"),
+            Self::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+/// Every permission granted by at least one of `roles`
+pub fn permissions(roles: &[Role]) -> Vec<Permission> {
+    let mut permissions = roles.iter()
+                               .flat_map(|role| role.permissions())
+                               .copied()
+                               .collect::<Vec<_>>();
+    permissions.sort();
+    permissions.dedup();
+    permissions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Permission::*;
+
+    #[test]
+    fn grants_permissions_per_role() {
+        assert_eq!(Role::Viewer.permissions(), [Read]);
+        assert_eq!(Role::Presenter.permissions(), [Read, Present]);
+        assert_eq!(Role::Moderator.permissions(), [Read, Moderate, Export]);
+        assert_eq!(Role::Admin.permissions(), [Read, Moderate, Export, Delete, Audit, Present]);
+
+        assert_eq!(permissions(&[Role::Viewer, Role::Moderator]), [Read, Moderate, Export]);
+        assert!(permissions(&[]).is_empty());
+    }
+
+    #[test]
+    fn parses_roles() {
+        assert_eq!(Role::parse_all(&[]).unwrap(), [Role::Viewer]);
+        assert_eq!(Role::parse_all(&["admin", "viewer", "admin"]).unwrap(), [Role::Viewer, Role::Admin]);
+        assert_eq!(Role::parse_all(&["moderator", "presenter"]).unwrap(), [Role::Presenter, Role::Moderator]);
+        assert!(Role::parse_all(&["owner"]).is_err());
+        assert_eq!(Role::join(&[Role::Viewer, Role::Moderator]), "viewer,moderator");
+    }
+}
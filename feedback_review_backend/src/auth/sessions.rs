@@ -0,0 +1,149 @@
+use crate::auth::{random_hex, sha256_hex};
+use crate::store::JsonStore;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::collections::HashMap;
+use std::env;
+use std::sync::LazyLock;
+
+const SESSION_FILE: &str = "sessions.json";
+
+pub static SESSION_TTL: LazyLock<Duration> = LazyLock::new(|| {
+    const ENV_KEY: &str = "SESSION_TTL_HOURS";
+    const DEFAULT_HOURS: i64 = 12;
+
+    let hours = match env::var(ENV_KEY) {
+        Ok(hours) => {
+            hours.parse()
+                 .ok()
+                 .filter(|hours| *hours > 0)
+                 .unwrap_or_else(|| {
+                     println!("WARNING: {ENV_KEY} is set, but the value is invalid, \
+                         using default ({DEFAULT_HOURS})");
+                     DEFAULT_HOURS
+                 })
+        }
+        Err(_) => DEFAULT_HOURS,
+    };
+
+    Duration::hours(hours)
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Session {
+    username: String,
+    created: DateTime<Utc>,
+    expires: DateTime<Utc>,
+}
+
+/// Sessions are persisted (keyed by the SHA-256 of the session id),
+/// so they survive restarts and can be revoked from the CLI
+pub struct SessionStore {
+    store: JsonStore<HashMap<String, Session>>,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        Self { store: JsonStore::new(SESSION_FILE) }
+    }
+
+    pub fn in_dir(dir: &Path) -> Self {
+        Self { store: JsonStore::at(dir.join(SESSION_FILE)) }
+    }
+
+    /// Returns the session id for the cookie and when it expires
+    pub async fn create(&self, username: &str) -> Result<(String, DateTime<Utc>)> {
+        let id = random_hex();
+        let now = Utc::now();
+        let expires = now + *SESSION_TTL;
+        let session = Session { username: username.to_string(), created: now, expires };
+        let hash = sha256_hex(&id);
+
+        self.store
+            .update(|sessions| {
+                sessions.retain(|_, session| session.expires > now);
+                sessions.insert(hash, session);
+                Ok(())
+            })
+            .await?;
+
+        Ok((id, expires))
+    }
+
+    /// Returns the username, the roles are looked up on every request so changes apply immediately
+    pub async fn verify(&self, id: &str) -> Option<String> {
+        let hash = sha256_hex(id);
+        self.store
+            .read(|sessions| {
+                sessions.get(&hash)
+                        .filter(|session| session.expires > Utc::now())
+                        .map(|session| session.username.clone())
+            })
+            .await
+    }
+
+    pub async fn revoke(&self, id: &str) -> Result<()> {
+        let hash = sha256_hex(id);
+        self.store
+            .update(|sessions| {
+                sessions.remove(&hash);
+                Ok(())
+            })
+            .await
+    }
+
+    /// Logs a user out everywhere, returns how many sessions were revoked
+    pub async fn revoke_user(&self, username: &str) -> Result<usize> {
+        self.store
+            .update(|sessions| {
+                let before = sessions.len();
+                sessions.retain(|_, session| session.username != username);
+                Ok(before - sessions.len())
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_root;
+
+    #[tokio::test]
+    async fn expires_and_revokes_sessions() {
+        let root = temp_root("session_store");
+        let sessions = SessionStore::in_dir(&root);
+
+        let (id, expires) = sessions.create("alice").await.unwrap();
+        assert!(expires > Utc::now());
+        assert_eq!(sessions.verify(&id).await.as_deref(), Some("alice"));
+        assert_eq!(sessions.verify("not a session").await, None);
+
+        // Only the hash is stored, a leaked file doesn't contain usable session ids
+        let stored = std::fs::read_to_string(root.join(SESSION_FILE)).unwrap();
+        assert!(!stored.contains(&id));
+        assert!(stored.contains(&sha256_hex(&id)));
+
+        sessions.store
+            .update(|sessions| {
+                for session in sessions.values_mut() {
+                    session.expires = Utc::now() - Duration::seconds(1);
+                }
+                Ok(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(sessions.verify(&id).await, None);
+
+        let (first, _) = sessions.create("alice").await.unwrap();
+        let (second, _) = sessions.create("bob").await.unwrap();
+        sessions.revoke(&first).await.unwrap();
+        assert_eq!(sessions.verify(&first).await, None);
+        assert_eq!(sessions.revoke_user("bob").await.unwrap(), 1);
+        assert_eq!(sessions.verify(&second).await, None);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
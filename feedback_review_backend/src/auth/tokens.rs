@@ -0,0 +1,115 @@
+use crate::auth::roles::Role;
+use crate::auth::{random_hex, sha256_hex, Identity};
+use crate::store::JsonStore;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const TOKEN_FILE: &str = "tokens.json";
+const TOKEN_PREFIX: &str = "frb_";
+
+/// Only the SHA-256 of a token is stored, the token itself is printed once when it is created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    name: String,
+    hash: String,
+    created: DateTime<Utc>,
+    #[serde(default = "Role::default_roles")]
+    roles: Vec<Role>,
+}
+
+pub struct TokenStore {
+    store: JsonStore<Vec<StoredToken>>,
+}
+
+impl TokenStore {
+    pub fn new() -> Self {
+        Self { store: JsonStore::new(TOKEN_FILE) }
+    }
+
+    pub fn in_dir(dir: &Path) -> Self {
+        Self { store: JsonStore::at(dir.join(TOKEN_FILE)) }
+    }
+
+    pub async fn verify(&self, token: &str) -> Option<Identity> {
+        let hash = sha256_hex(token);
+        self.store
+            .read(|tokens| {
+                tokens.iter()
+                      .find(|stored| stored.hash == hash)
+                      .map(|stored| Identity { name: stored.name.clone(), roles: stored.roles.clone() })
+            })
+            .await
+    }
+
+    /// Returns the new token, it can't be recovered later
+    pub async fn add(&self, name: &str, roles: Vec<Role>) -> Result<String> {
+        let token = format!("{TOKEN_PREFIX}{}", random_hex());
+        let hash = sha256_hex(&token);
+
+        self.store
+            .update(|tokens| {
+                if tokens.iter().any(|stored| stored.name == name) {
+                    bail!("A token named {name} already exists, revoke it first");
+                }
+                tokens.push(StoredToken { name: name.to_string(), hash, created: Utc::now(), roles });
+                Ok(())
+            })
+            .await?;
+
+        Ok(token)
+    }
+
+    pub async fn list(&self) -> Vec<(String, DateTime<Utc>, Vec<Role>)> {
+        self.store
+            .read(|tokens| {
+                tokens.iter()
+                      .map(|stored| (stored.name.clone(), stored.created, stored.roles.clone()))
+                      .collect()
+            })
+            .await
+    }
+
+    pub async fn revoke(&self, name: &str) -> Result<()> {
+        self.store
+            .update(|tokens| {
+                let Some(pos) = tokens.iter().position(|stored| stored.name == name) else {
+                    bail!("No token named {name}");
+                };
+                tokens.remove(pos);
+                Ok(())
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_root;
+
+    #[tokio::test]
+    async fn stores_only_token_hashes() {
+        let root = temp_root("token_store");
+        let tokens = TokenStore::in_dir(&root);
+
+        let token = tokens.add("ci", vec![Role::Moderator]).await.unwrap();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        let stored = std::fs::read_to_string(root.join(TOKEN_FILE)).unwrap();
+        assert!(!stored.contains(&token));
+        assert!(stored.contains(&sha256_hex(&token)));
+
+        let identity = tokens.verify(&token).await.unwrap();
+        assert_eq!(identity.name, "ci");
+        assert_eq!(identity.roles, [Role::Moderator]);
+        assert!(tokens.verify(&sha256_hex(&token)).await.is_none());
+        assert!(tokens.add("ci", vec![]).await.is_err());
+
+        tokens.revoke("ci").await.unwrap();
+        assert!(tokens.verify(&token).await.is_none());
+        assert!(tokens.revoke("ci").await.is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+use crate::auth::roles::Role;
+use crate::store::JsonStore;
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::task;
+
+const USER_FILE: &str = "users.json";
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// `password_hash` is an argon2id PHC string, it contains its own salt and parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Account {
+    username: String,
+    password_hash: String,
+    created: DateTime<Utc>,
+    #[serde(default = "Role::default_roles")]
+    roles: Vec<Role>,
+}
+
+pub struct UserStore {
+    store: JsonStore<Vec<Account>>,
+}
+
+impl UserStore {
+    pub fn new() -> Self {
+        Self { store: JsonStore::new(USER_FILE) }
+    }
+
+    pub fn in_dir(dir: &Path) -> Self {
+        Self { store: JsonStore::at(dir.join(USER_FILE)) }
+    }
+
+    pub async fn verify(&self, username: &str, password: &str) -> bool {
+        let Some(password_hash) = self.store
+                                      .read(|accounts| {
+                                          accounts.iter()
+                                                  .find(|account| account.username == username)
+                                                  .map(|account| account.password_hash.clone())
+                                      })
+                                      .await else {
+            return false;
+        };
+
+        let password = password.to_string();
+        // Hashing takes a while on purpose, keep it away from the async workers
+        task::spawn_blocking(move || {
+            PasswordHash::new(&password_hash)
+                .is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        })
+            .await
+            .unwrap_or(false)
+    }
+
+    /// `None` if the user doesn't exist (anymore)
+    pub async fn roles(&self, username: &str) -> Option<Vec<Role>> {
+        self.store
+            .read(|accounts| {
+                accounts.iter()
+                        .find(|account| account.username == username)
+                        .map(|account| account.roles.clone())
+            })
+            .await
+    }
+
+    pub async fn add(&self, username: &str, password: &str, roles: Vec<Role>) -> Result<()> {
+        let password_hash = hash_password(password).await?;
+        self.store
+            .update(|accounts| {
+                if accounts.iter().any(|account| account.username == username) {
+                    bail!("A user named {username} already exists");
+                }
+                accounts.push(Account {
+                    username: username.to_string(),
+                    password_hash,
+                    created: Utc::now(),
+                    roles,
+                });
+                Ok(())
+            })
+            .await
+    }
+
+    pub async fn set_password(&self, username: &str, password: &str) -> Result<()> {
+        let password_hash = hash_password(password).await?;
+        self.store
+            .update(|accounts| {
+                let Some(account) = accounts.iter_mut().find(|account| account.username == username) else {
+                    bail!("No user named {username}");
+                };
+                account.password_hash = password_hash;
+                Ok(())
+            })
+            .await
+    }
+
+    pub async fn set_roles(&self, username: &str, roles: Vec<Role>) -> Result<()> {
+        self.store
+            .update(|accounts| {
+                let Some(account) = accounts.iter_mut().find(|account| account.username == username) else {
+                    bail!("No user named {username}");
+                };
+                account.roles = roles;
+                Ok(())
+            })
+            .await
+    }
+
+    pub async fn list(&self) -> Vec<(String, DateTime<Utc>, Vec<Role>)> {
+        self.store
+            .read(|accounts| {
+                accounts.iter()
+                        .map(|account| (account.username.clone(), account.created, account.roles.clone()))
+                        .collect()
+            })
+            .await
+    }
+
+    pub async fn remove(&self, username: &str) -> Result<()> {
+        self.store
+            .update(|accounts| {
+                let Some(pos) = accounts.iter().position(|account| account.username == username) else {
+                    bail!("No user named {username}");
+                };
+                accounts.remove(pos);
+                Ok(())
+            })
+            .await
+    }
+}
+
+async fn hash_password(password: &str) -> Result<String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        bail!("The password needs at least {MIN_PASSWORD_LEN} characters");
+    }
+
+    let password = password.to_string();
+    task::spawn_blocking(move || {
+        let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>())
+            .map_err(|e| anyhow!("Failed to encode salt: {e}"))?;
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| anyhow!("Failed to hash password: {e}"))
+    })
+        .await
+        .context("Password hashing panicked")?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_root;
+
+    #[tokio::test]
+    async fn verifies_argon2_passwords() {
+        let root = temp_root("user_store");
+        let users = UserStore::in_dir(&root);
+
+        assert!(users.add("alice", "short", vec![]).await.is_err());
+        users.add("alice", "correct horse", vec![Role::Moderator]).await.unwrap();
+        assert!(users.add("alice", "another password", vec![]).await.is_err());
+
+        let stored = std::fs::read_to_string(root.join(USER_FILE)).unwrap();
+        assert!(stored.contains("$argon2id$"));
+        assert!(!stored.contains("correct horse"));
+
+        assert!(users.verify("alice", "correct horse").await);
+        assert!(!users.verify("alice", "correct horse ").await);
+        assert!(!users.verify("bob", "correct horse").await);
+        assert_eq!(users.roles("alice").await.unwrap(), [Role::Moderator]);
+
+        users.set_password("alice", "battery staple").await.unwrap();
+        assert!(!users.verify("alice", "correct horse").await);
+        assert!(users.verify("alice", "battery staple").await);
+
+        users.remove("alice").await.unwrap();
+        assert!(!users.verify("alice", "battery staple").await);
+        assert_eq!(users.roles("alice").await, None);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
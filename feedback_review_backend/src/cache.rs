@@ -0,0 +1,203 @@
+use crate::days::FileState;
+use crate::storage::DayDate;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use std::time::UNIX_EPOCH;
+
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+/// Days before today (UTC) don't get new entries, but an entry can still be deleted.
+/// Copies are kept for a few minutes instead of `immutable`, so a deletion shows up soon.
+const CLOSED_DAY_CACHE: &str = "private, max-age=300, must-revalidate";
+/// Today's file still grows, so clients revalidate every time
+const OPEN_DAY_CACHE: &str = "private, no-cache";
+
+/// `ETag` and `Last-Modified` of a day file, derived from its length and modification time
+#[derive(Debug, Clone)]
+pub struct Validators {
+    etag: String,
+    last_modified: DateTime<Utc>,
+    closed: bool,
+}
+
+impl Validators {
+    pub fn of(date: DayDate, (modified, len): FileState) -> Self {
+        let modified = modified.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                               .unwrap_or_default();
+        let last_modified = DateTime::from_timestamp(modified.as_secs() as i64, 0).unwrap_or_default();
+
+        Self {
+            etag: format!("\"{:x}-{:x}\"", len, modified.as_nanos()),
+            last_modified,
+            closed: date < DayDate::today(),
+        }
+    }
+
+    /// Whether the client's copy is still current. `If-None-Match` wins over `If-Modified-Since`.
+    pub fn fresh(&self, headers: &HeaderMap) -> bool {
+        if let Some(tags) = headers.get(header::IF_NONE_MATCH).and_then(|tags| tags.to_str().ok()) {
+            return tags.split(',')
+                       .map(|tag| tag.trim().trim_start_matches("W/"))
+                       .any(|tag| tag == "*" || tag == self.etag);
+        }
+
+        headers.get(header::IF_MODIFIED_SINCE)
+               .and_then(|since| since.to_str().ok())
+               .and_then(|since| DateTime::parse_from_rfc2822(since).ok())
+               .is_some_and(|since| self.last_modified <= since)
+    }
+
+    /// 304 with the same validators as the full response
+    pub fn not_modified(&self) -> Response {
+        self.apply(StatusCode::NOT_MODIFIED.into_response())
+    }
+
+    pub fn apply(&self, mut response: Response) -> Response {
+        let headers = response.headers_mut();
+        let values = [
+            (header::ETAG, self.etag.clone()),
+            (header::LAST_MODIFIED, self.last_modified.format(HTTP_DATE_FORMAT).to_string()),
+            (header::CACHE_CONTROL, String::from(if self.closed { CLOSED_DAY_CACHE } else { OPEN_DAY_CACHE })),
+        ];
+        for (name, value) in values {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.insert(name, value);
+            }
+        }
+        // Another reviewer on the same browser must not get this copy
+        headers.append(header::VARY, HeaderValue::from_static("Authorization, Cookie"));
+        response
+    }
+}
+
+/// Adds the validators to a full response, if the day file had any
+pub fn with_validators(validators: Option<Validators>, response: Response) -> Response {
+    match validators {
+        Some(validators) => validators.apply(response),
+        None => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FILE_SUFFIX;
+    use crate::days::DayIndex;
+    use crate::storage::Storage;
+    use crate::test_support::{app, app_with, send, temp_root};
+    use axum::body::{self, Body};
+    use axum::http::{header, Request, StatusCode};
+    use std::io::Write;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn validators(etag: &str) -> Validators {
+        Validators {
+            etag: etag.to_string(),
+            last_modified: "2024-05-01T12:00:00Z".parse().unwrap(),
+            closed: true,
+        }
+    }
+
+    #[test]
+    fn checks_conditional_headers() {
+        let validators = validators("\"a-1\"");
+        let headers = |name, value| HeaderMap::from_iter([(name, HeaderValue::from_static(value))]);
+
+        assert!(validators.fresh(&headers(header::IF_NONE_MATCH, "\"b-2\", W/\"a-1\"")));
+        assert!(validators.fresh(&headers(header::IF_NONE_MATCH, "*")));
+        assert!(!validators.fresh(&headers(header::IF_NONE_MATCH, "\"b-2\"")));
+        assert!(validators.fresh(&headers(header::IF_MODIFIED_SINCE, "Wed, 01 May 2024 12:00:00 GMT")));
+        assert!(!validators.fresh(&headers(header::IF_MODIFIED_SINCE, "Wed, 01 May 2024 11:59:59 GMT")));
+        assert!(!validators.fresh(&headers(header::IF_MODIFIED_SINCE, "yesterday")));
+        assert!(!validators.fresh(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn revalidates_day_files() {
+        let root = temp_root("etag");
+        let dashes = "-".repeat(50);
+        let file = root.join(format!("2024-05-01{FILE_SUFFIX}"));
+        std::fs::write(&file, format!("{dashes}\n[2024-05-01 - 12:00:00]z\nfirst\n{dashes}\n\n")).unwrap();
+
+        let get = |uri: &str, condition: Option<(header::HeaderName, String)>| {
+            let mut req = Request::get(uri);
+            if let Some((name, value)) = condition {
+                req = req.header(name, value);
+            }
+            app(&root).oneshot(req.body(Body::empty()).unwrap())
+        };
+
+        for uri in ["/feedback/2024-05-01", "/feedback/2024-05-01/entries"] {
+            let res = get(uri, None).await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            let etag = res.headers()[header::ETAG].to_str().unwrap().to_string();
+            let last_modified = res.headers()[header::LAST_MODIFIED].to_str().unwrap().to_string();
+            assert_eq!(res.headers()[header::CACHE_CONTROL], CLOSED_DAY_CACHE);
+
+            let res = get(uri, Some((header::IF_NONE_MATCH, etag.clone()))).await.unwrap();
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED, "{uri}");
+            assert_eq!(res.headers()[header::ETAG], etag.as_str());
+            assert!(body::to_bytes(res.into_body(), usize::MAX).await.unwrap().is_empty());
+
+            let res = get(uri, Some((header::IF_MODIFIED_SINCE, last_modified))).await.unwrap();
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED, "{uri}");
+
+            let res = get(uri, Some((header::IF_NONE_MATCH, String::from("\"other\"")))).await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK, "{uri}");
+        }
+
+        let res = get("/feedback/2024-05-01/entries", None).await.unwrap();
+        let etag = res.headers()[header::ETAG].to_str().unwrap().to_string();
+        std::fs::OpenOptions::new().append(true).open(&file).unwrap().write_all(b"more").unwrap();
+        let res = get("/feedback/2024-05-01/entries", Some((header::IF_NONE_MATCH, etag))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = get("/feedback/2024-05-01/entries?tag=bug", None).await.unwrap();
+        assert!(!res.headers().contains_key(header::ETAG));
+
+        let res = get("/feedback/2024-05-02", None).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let today = DayDate::today();
+        std::fs::write(root.join(today.file_name()), format!("{dashes}\n[{today} - 12:00:00]z\nnew\n{dashes}\n\n")).unwrap();
+        let res = get(&format!("/feedback/{today}/entries"), None).await.unwrap();
+        assert_eq!(res.headers()[header::CACHE_CONTROL], OPEN_DAY_CACHE);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn stops_matching_after_a_deletion() {
+        let root = temp_root("etag_deletion");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-05-01{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-01 - 12:00:00]z\nfirst\n{dashes}\n\n{dashes}\n[2024-05-01 - 13:00:00]z\nsecond\n{dashes}\n\n"),
+        ).unwrap();
+        // Shared like in the server, the deletion refreshes the index through `rewrite_day`
+        let days = Arc::new(DayIndex::new(Storage::new(&root)));
+        let get = |etag: Option<String>| {
+            let mut req = Request::get("/feedback/2024-05-01/entries");
+            if let Some(etag) = etag {
+                req = req.header(header::IF_NONE_MATCH, etag);
+            }
+            app_with(&root, "tester", days.clone()).oneshot(req.body(Body::empty()).unwrap())
+        };
+
+        let res = get(None).await.unwrap();
+        let etag = res.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert_eq!(get(Some(etag.clone())).await.unwrap().status(), StatusCode::NOT_MODIFIED);
+
+        let (status, _) = send(app_with(&root, "tester", days.clone()), "DELETE", "/feedback/2024-05-01/entries/1", r#"{"reason":"Erasure request"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let res = get(Some(etag.clone())).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_ne!(res.headers()[header::ETAG], etag.as_str());
+        let entries: serde_json::Value = serde_json::from_slice(&body::to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(entries.as_array().unwrap().len(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,366 @@
+use crate::feedback::{self, Entry};
+use crate::storage::{DayDate, Storage};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::io;
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{broadcast, mpsc, Mutex, OnceCell, RwLock};
+use tracing::{debug, error, info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// New entries a slow subscriber may fall behind by before it misses some
+const UPDATES_CAPACITY: usize = 256;
+/// `notify` (inotify on Linux, falls back to polling if that fails) or `poll`.
+/// Bind mounts from Docker Desktop on macOS don't deliver inotify events, those need `poll`.
+static WATCH_MODE: LazyLock<WatchMode> = LazyLock::new(|| {
+    const ENV_KEY: &str = "WATCH_MODE";
+    const DEFAULT_MODE: WatchMode = WatchMode::Notify;
+
+    match env::var(ENV_KEY).as_deref() {
+        Ok("notify") => WatchMode::Notify,
+        Ok("poll") => WatchMode::Poll,
+        Ok(_) => {
+            println!("WARNING: {ENV_KEY} is set, but the value is invalid, using default (notify)");
+            DEFAULT_MODE
+        }
+        Err(_) => DEFAULT_MODE,
+    }
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WatchMode {
+    Notify,
+    Poll,
+}
+
+/// Modification time and length of a day file, a day is parsed again when they change
+pub type FileState = (Option<SystemTime>, u64);
+
+/// A parsed day file
+#[derive(Debug)]
+pub struct Day {
+    pub state: FileState,
+    pub entries: Vec<Entry>,
+}
+
+/// A change to the entries of a loaded day, announced to the subscribers of [`DayIndex::subscribe`]
+#[derive(Debug, Clone)]
+pub enum Update {
+    /// Appended to a day file
+    Added(Entry),
+    /// Replaced by a tombstone, or its whole day file is gone
+    Removed(RemovedEntry),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedEntry {
+    pub id: String,
+    pub date: DayDate,
+}
+
+/// Every day file, parsed once and kept in memory.
+/// Loaded on first use and kept fresh by [`DayIndex::watch`], which only re-reads files that changed.
+pub struct DayIndex {
+    storage: Storage,
+    days: RwLock<BTreeMap<DayDate, Arc<Day>>>,
+    loaded: OnceCell<()>,
+    /// Reloads run one at a time, so an older read never replaces a newer one
+    reload: Mutex<()>,
+    /// Entries that were appended or deleted after the first load
+    updates: broadcast::Sender<Update>,
+}
+
+impl DayIndex {
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            storage,
+            days: RwLock::new(BTreeMap::new()),
+            loaded: OnceCell::new(),
+            reload: Mutex::new(()),
+            updates: broadcast::channel(UPDATES_CAPACITY).0,
+        }
+    }
+
+    /// Every entry appended or deleted from now on, in the order the watcher saw them
+    pub fn subscribe(&self) -> broadcast::Receiver<Update> {
+        self.updates.subscribe()
+    }
+
+    /// Replaces a loaded day and announces the entries that weren't there before and the ones that are gone.
+    /// Rewrites only ever replace entries with tombstones, so new entries have higher indices
+    /// and known entries that are missing were deleted.
+    fn store(&self, days: &mut BTreeMap<DayDate, Arc<Day>>, date: DayDate, day: Day) {
+        // Everything is new on the first load
+        if self.loaded.initialized() {
+            let previous = days.get(&date).map(|day| day.entries.as_slice()).unwrap_or_default();
+            let kept = day.entries.iter().map(|entry| entry.index).collect::<BTreeSet<_>>();
+            let removed = previous.iter()
+                                  .filter(|entry| !kept.contains(&entry.index))
+                                  .map(|entry| Update::Removed(RemovedEntry { id: entry.id.clone(), date }));
+            let known = previous.last().map(|entry| entry.index);
+            let added = day.entries
+                           .iter()
+                           .filter(|entry| known.is_none_or(|known| entry.index > known))
+                           .map(|entry| Update::Added(entry.clone()));
+            for update in removed.chain(added) {
+                // Only fails without subscribers
+                let _ = self.updates.send(update);
+            }
+        }
+        days.insert(date, Arc::new(day));
+    }
+
+    /// Drops a day whose file is gone and announces each of its entries as removed
+    fn forget(&self, days: &mut BTreeMap<DayDate, Arc<Day>>, date: DayDate) {
+        let Some(day) = days.remove(&date) else {
+            return;
+        };
+        debug!(%date, entries = day.entries.len(), "Dropped day");
+        for entry in &day.entries {
+            // Only fails without subscribers
+            let _ = self.updates.send(Update::Removed(RemovedEntry { id: entry.id.clone(), date }));
+        }
+    }
+
+    async fn loaded(&self) -> io::Result<()> {
+        self.loaded.get_or_try_init(|| self.sync()).await.map(|_| ())
+    }
+
+    /// Compares every day file with what is loaded, parses the changed ones and drops vanished ones
+    pub async fn sync(&self) -> io::Result<()> {
+        let _reload = self.reload.lock().await;
+
+        let mut current = BTreeMap::new();
+        for date in self.storage.days().await? {
+            if let Some(meta) = self.storage.day_metadata(date).await? {
+                current.insert(date, (meta.modified().ok(), meta.len()));
+            }
+        }
+
+        let changed = {
+            let days = self.days.read().await;
+            current.iter()
+                   .filter(|(date, state)| days.get(date).is_none_or(|day| day.state != **state))
+                   .map(|(date, _)| *date)
+                   .collect::<Vec<_>>()
+        };
+
+        let mut parsed = Vec::with_capacity(changed.len());
+        for date in changed {
+            if let Some(day) = self.read(date).await? {
+                parsed.push((date, day));
+            }
+        }
+
+        let mut days = self.days.write().await;
+        let vanished = days.keys()
+                           .filter(|date| !current.contains_key(date))
+                           .copied()
+                           .collect::<Vec<_>>();
+        for date in vanished {
+            self.forget(&mut days, date);
+        }
+        for (date, day) in parsed {
+            debug!(%date, entries = day.entries.len(), "Loaded day");
+            self.store(&mut days, date, day);
+        }
+
+        Ok(())
+    }
+
+    /// [`Storage::rewrite_day`], then reloads the day so the next request already sees the change
+    pub async fn rewrite_day(
+        &self,
+        date: DayDate,
+        f: impl FnOnce(&str) -> Option<String> + Send + 'static,
+    ) -> io::Result<bool> {
+        let rewritten = self.storage.rewrite_day(date, f).await?;
+        // The file is rewritten either way, the watcher retries on its next event
+        if rewritten && let Err(e) = self.refresh(date).await {
+            error!("Failed to reload day {date}: {e}");
+        }
+        Ok(rewritten)
+    }
+
+    /// Reads a single day again, e.g. after it was rewritten or the watcher saw it change
+    pub async fn refresh(&self, date: DayDate) -> io::Result<()> {
+        let _reload = self.reload.lock().await;
+
+        let day = self.read(date).await?;
+        let mut days = self.days.write().await;
+        match day {
+            Some(day) => {
+                debug!(%date, entries = day.entries.len(), "Reloaded day");
+                self.store(&mut days, date, day);
+            }
+            None => self.forget(&mut days, date),
+        }
+        Ok(())
+    }
+
+    async fn read(&self, date: DayDate) -> io::Result<Option<Day>> {
+        let Some(meta) = self.storage.day_metadata(date).await? else {
+            return Ok(None);
+        };
+        let Some(content) = self.storage.read_day(date).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Day {
+            state: (meta.modified().ok(), meta.len()),
+            entries: feedback::parse_day(date, &content),
+        }))
+    }
+
+    /// Every day with a file, newest first
+    pub async fn days(&self) -> io::Result<Vec<DayDate>> {
+        self.loaded().await?;
+        Ok(self.days.read().await.keys().rev().copied().collect())
+    }
+
+    pub async fn day(&self, date: DayDate) -> io::Result<Option<Arc<Day>>> {
+        self.loaded().await?;
+        Ok(self.days.read().await.get(&date).cloned())
+    }
+
+    /// Every day as it is loaded right now
+    pub async fn all(&self) -> io::Result<BTreeMap<DayDate, Arc<Day>>> {
+        self.loaded().await?;
+        Ok(self.days.read().await.clone())
+    }
+
+    /// Keeps the index fresh in the background, with inotify if possible and polling otherwise
+    pub fn watch(self: &Arc<Self>) {
+        if *WATCH_MODE == WatchMode::Notify {
+            match self.start_notify() {
+                Ok(()) => {
+                    info!("Watching {} for changes", self.storage.root().display());
+                    return;
+                }
+                Err(e) => warn!("Failed to watch {}, polling instead: {e}", self.storage.root().display()),
+            }
+        }
+
+        info!("Polling {} for changes every {}s", self.storage.root().display(), POLL_INTERVAL.as_secs());
+        let index = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(POLL_INTERVAL).await;
+                if let Err(e) = index.sync().await {
+                    error!("Failed to sync the day index: {e}");
+                }
+            }
+        });
+    }
+
+    fn start_notify(self: &Arc<Self>) -> notify::Result<()> {
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let mut watcher = RecommendedWatcher::new(
+            move |event: notify::Result<Event>| {
+                // Only fails once the receiving task is gone
+                let _ = events_tx.send(event);
+            },
+            notify::Config::default(),
+        )?;
+        watcher.watch(self.storage.root(), RecursiveMode::NonRecursive)?;
+
+        let index = self.clone();
+        tokio::spawn(async move {
+            // Dropping the watcher stops it
+            let _watcher = watcher;
+            while let Some(event) = events.recv().await {
+                // An append is several events, every changed day is only read once
+                let mut changed = BTreeSet::new();
+                let mut rescan = false;
+                for event in std::iter::once(event).chain(std::iter::from_fn(|| events.try_recv().ok())) {
+                    match event {
+                        Ok(event) if event.need_rescan() => rescan = true,
+                        Ok(event) => changed.extend(
+                            event.paths
+                                 .iter()
+                                 .filter_map(|path| path.file_name()?.to_str())
+                                 .filter_map(DayDate::from_file_name),
+                        ),
+                        Err(e) => {
+                            warn!("File watcher failed: {e}");
+                            rescan = true;
+                        }
+                    }
+                }
+
+                let result = if rescan {
+                    index.sync().await
+                } else {
+                    let mut result = Ok(());
+                    for date in changed {
+                        result = result.and(index.refresh(date).await);
+                    }
+                    result
+                };
+                if let Err(e) = result {
+                    error!("Failed to update the day index: {e}");
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_root;
+    use crate::FILE_SUFFIX;
+
+    #[tokio::test]
+    async fn reloads_changed_days() {
+        let root = temp_root("days");
+        let dashes = "-".repeat(50);
+        let entry = |body: &str| format!("{dashes}\n[2024-05-01 - 12:00:00]z\n{body}\n{dashes}\n\n");
+        let file = |date: &str| root.join(format!("{date}{FILE_SUFFIX}"));
+        std::fs::write(file("2024-05-01"), entry("first")).unwrap();
+        std::fs::write(file("2024-05-02"), entry("other day")).unwrap();
+
+        let index = DayIndex::new(Storage::new(&root));
+        let date = "2024-05-01".parse().unwrap();
+        assert_eq!(index.days().await.unwrap().len(), 2);
+        let loaded = index.day(date).await.unwrap().unwrap();
+        assert_eq!(loaded.entries.len(), 1);
+
+        // Nothing changed, the parsed day is kept
+        index.sync().await.unwrap();
+        assert!(Arc::ptr_eq(&loaded, &index.day(date).await.unwrap().unwrap()));
+
+        let mut updates = index.subscribe();
+        std::fs::write(file("2024-05-01"), entry("first") + &entry("second")).unwrap();
+        std::fs::remove_file(file("2024-05-02")).unwrap();
+        index.sync().await.unwrap();
+        assert_eq!(index.days().await.unwrap(), [date]);
+        assert_eq!(index.day(date).await.unwrap().unwrap().entries[1].body, "second");
+
+        let mut announced = vec![];
+        while let Ok(update) = updates.try_recv() {
+            announced.push(match update {
+                Update::Added(entry) => format!("added {}", entry.id),
+                Update::Removed(removed) => format!("removed {}", removed.id),
+            });
+        }
+        assert_eq!(announced, ["removed 2024-05-02.0", "added 2024-05-01.1"]);
+
+        // A single day the watcher saw disappear
+        std::fs::remove_file(file("2024-05-01")).unwrap();
+        index.refresh(date).await.unwrap();
+        assert!(index.days().await.unwrap().is_empty());
+        let removed = [updates.try_recv().unwrap(), updates.try_recv().unwrap()].map(|update| match update {
+            Update::Removed(removed) => removed.id,
+            Update::Added(entry) => panic!("{} wasn't added", entry.id),
+        });
+        assert_eq!(removed, ["2024-05-01.0", "2024-05-01.1"]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+use crate::annotations::{Annotations, EntryIndex};
+use crate::audit::{Action, AuditEvent, AuditLog};
+use crate::auth::Identity;
+use crate::feedback;
+use crate::days::DayIndex;
+use crate::storage::{DayDate, InvalidInput};
+use crate::EntriesError;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::Utc;
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{error, info};
+
+const MAX_REASON_LEN: usize = 1000;
+
+/// Why the entry is deleted, e.g. the ticket of the erasure request. It goes into the audit log.
+#[derive(Debug, Deserialize)]
+pub struct DeleteRequest {
+    reason: String,
+}
+
+/// Replaces the entry with a tombstone in its day file, drops its annotations
+/// and records who deleted it, when and why in the audit log
+pub async fn delete_entry(
+    State(days): State<Arc<DayIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    State(audit): State<Arc<AuditLog>>,
+    Extension(identity): Extension<Identity>,
+    date: DayDate,
+    EntryIndex(index): EntryIndex,
+    Json(request): Json<DeleteRequest>,
+) -> Response {
+    let reason = request.reason.trim().to_string();
+    if reason.is_empty() || reason.chars().count() > MAX_REASON_LEN {
+        return InvalidInput::new(
+            "invalid_reason",
+            format!("A reason of 1 to {MAX_REASON_LEN} characters is required"),
+            reason,
+        ).into_response();
+    }
+
+    let at = Utc::now();
+    match days.rewrite_day(date, move |content| feedback::tombstone(date, content, index, at)).await {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::NOT_FOUND, Json(EntriesError { error: "not_found" })).into_response(),
+        Err(e) => {
+            error!("Failed to delete {date}.{index}: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "write_failed" })).into_response();
+        }
+    }
+
+    let event = AuditEvent {
+        at,
+        actor: identity.name,
+        action: Action::DeleteEntry,
+        target: format!("{date}.{index}"),
+        reason,
+    };
+    info!(entry = event.target, by = event.actor, reason = event.reason, "Deleted entry");
+
+    if let Err(e) = annotations.remove(date, index).await {
+        error!("Failed to remove the annotations of {date}.{index}: {e:#}");
+    }
+
+    if let Err(e) = audit.record(&event).await {
+        // The entry is gone already, the log line above is all that is left of the event
+        error!(?event, "Failed to write the audit log: {e}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "audit_failed" })).into_response();
+    }
+
+    Json(event).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{app, fetch, send, temp_root};
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn deletes_entries_with_tombstones() {
+        let root = temp_root("delete");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-05-01{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-01 - 12:00:00]z\nmy mail is a@b.c\n{dashes}\n\n{dashes}\n[2024-05-01 - 13:00:00]z\nsecond\n{dashes}\n\n"),
+        ).unwrap();
+        send(app(&root), "POST", "/feedback/2024-05-01/entries/0/comments", r#"{"body":"Mail them at a@b.c"}"#).await;
+
+        let uri = "/feedback/2024-05-01/entries/0";
+        let (status, _) = send(app(&root), "DELETE", uri, r#"{"reason":" "}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) = send(app(&root), "DELETE", uri, r#"{"reason":"Erasure request #7"}"#).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let event: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(event["target"], "2024-05-01.0");
+        assert_eq!(event["actor"], "tester");
+
+        let content = std::fs::read_to_string(root.join(format!("2024-05-01{FILE_SUFFIX}"))).unwrap();
+        assert!(!content.contains("a@b.c"));
+        assert!(content.contains("[meta deleted="));
+
+        // The later entry keeps its id
+        let (_, body) = fetch(app(&root), "/feedback/2024-05-01/entries").await;
+        let entries: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(entries.as_array().unwrap().len(), 1);
+        assert_eq!(entries[0]["id"], "2024-05-01.1");
+
+        let (_, body) = fetch(app(&root), "/feedback/2024-05-01/annotations").await;
+        assert_eq!(body, "{}");
+
+        let (status, _) = send(app(&root), "DELETE", uri, r#"{"reason":"Again"}"#).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(app(&root), "PUT", &format!("{uri}/status"), r#"{"status":"read"}"#).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let log = std::fs::read_to_string(root.join("review_data").join("audit.log")).unwrap();
+        let lines = log.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1);
+        let logged: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(logged["action"], "delete_entry");
+        assert_eq!(logged["reason"], "Erasure request #7");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+use crate::days::{DayIndex, Update};
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::stream::{self, Stream};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+
+/// New entries as they arrive, as `entry` events with the entry as JSON and its id as event id.
+/// Deleted entries are `removed` events with their `id` and `date` as JSON.
+/// A client that falls behind gets a `lagged` event with the number of missed updates and should reload.
+pub async fn get_events(State(days): State<Arc<DayIndex>>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    debug!("Subscribing to new entries");
+
+    let updates = days.subscribe();
+    let events = stream::unfold(updates, |mut updates| async move {
+        let event = match updates.recv().await {
+            Ok(Update::Added(entry)) => Event::default()
+                .event("entry")
+                .id(entry.id.clone())
+                .json_data(&entry)
+                .unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+            Ok(Update::Removed(removed)) => Event::default()
+                .event("removed")
+                .json_data(&removed)
+                .unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+            Err(RecvError::Lagged(missed)) => {
+                warn!(missed, "Event subscriber fell behind");
+                Event::default().event("lagged").data(missed.to_string())
+            }
+            Err(RecvError::Closed) => return None,
+        };
+        Some((Ok(event), updates))
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::days::DayIndex;
+    use crate::FILE_SUFFIX;
+    use crate::storage::Storage;
+    use crate::test_support::{app_with, fetch, send, temp_root};
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn streams_new_and_deleted_entries() {
+        use futures_util::StreamExt;
+
+        let root = temp_root("events");
+        let dashes = "-".repeat(50);
+        let file = root.join(format!("2024-05-01{FILE_SUFFIX}"));
+        std::fs::write(&file, format!("{dashes}\n[2024-05-01 - 12:00:00]z\nalready there\n{dashes}\n\n")).unwrap();
+
+        let days = Arc::new(DayIndex::new(Storage::new(&root)));
+        let app = app_with(&root, "tester", days.clone());
+        let (status, _) = fetch(app.clone(), "/dates").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let res = app.oneshot(Request::get("/events").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::CONTENT_TYPE], "text/event-stream");
+        let mut events = res.into_body().into_data_stream();
+
+        let mut content = std::fs::read_to_string(&file).unwrap();
+        content.push_str(&format!("{dashes}\n[2024-05-01 - 13:00:00]z\njust arrived\n{dashes}\n\n"));
+        std::fs::write(&file, content).unwrap();
+        days.sync().await.unwrap();
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), events.next()).await.unwrap().unwrap().unwrap();
+        let event = String::from_utf8_lossy(&event);
+        assert!(event.contains("event: entry"));
+        assert!(event.contains("id: 2024-05-01.1"));
+        assert!(event.contains("just arrived"));
+        assert!(!event.contains("already there"));
+
+        let (status, _) = send(app_with(&root, "tester", days.clone()), "DELETE", "/feedback/2024-05-01/entries/0", r#"{"reason":"Erasure request"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), events.next()).await.unwrap().unwrap().unwrap();
+        let event = String::from_utf8_lossy(&event);
+        assert!(event.contains("event: removed"), "{event}");
+        assert!(event.contains(r#"{"id":"2024-05-01.0","date":"2024-05-01"}"#), "{event}");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,361 @@
+use crate::feedback::{self, Entry};
+use crate::storage::{DayDate, Storage};
+use axum::body::Bytes;
+use chrono::Datelike;
+use futures_util::stream::{self, Stream};
+use std::collections::VecDeque;
+use std::io;
+use std::str::FromStr;
+use tracing::{debug, error};
+
+const CSV_HEADER: &str = "id,date,timestamp,body,metadata\r\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+    Markdown,
+    /// The original day files
+    Zip,
+}
+
+struct State {
+    storage: Storage,
+    format: Format,
+    from: DayDate,
+    to: DayDate,
+    /// Oldest first, read one at a time
+    days: VecDeque<DayDate>,
+    started: bool,
+    done: bool,
+    entries_written: usize,
+    zip: ZipDirectory,
+}
+
+/// What the ZIP central directory needs to know about the files written so far
+#[derive(Default)]
+struct ZipDirectory {
+    offset: u64,
+    records: Vec<u8>,
+    count: u16,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Markdown => "md",
+            Self::Zip => "zip",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Json => "application/json",
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Zip => "application/zip",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            "md" | "markdown" => Ok(Self::Markdown),
+            "zip" => Ok(Self::Zip),
+            _ => Err(String::from("Expected one of csv, json, md or zip")),
+        }
+    }
+}
+
+/// Streams the export of `days`, only a single day file is held in memory at a time
+pub fn stream_export(
+    storage: Storage,
+    format: Format,
+    from: DayDate,
+    to: DayDate,
+    mut days: Vec<DayDate>,
+) -> impl Stream<Item = io::Result<Bytes>> {
+    days.sort_unstable();
+
+    let state = State {
+        storage,
+        format,
+        from,
+        to,
+        days: days.into(),
+        started: false,
+        done: false,
+        entries_written: 0,
+        zip: ZipDirectory::default(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+
+        if !state.started {
+            state.started = true;
+            return Some((Ok(state.header()), state));
+        }
+
+        match state.next_day().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), state)),
+            Ok(None) => {
+                state.done = true;
+                Some((Ok(state.footer()), state))
+            }
+            Err(e) => {
+                // The status is already sent, aborting the body is all that's left
+                error!("Failed to stream export: {e}");
+                state.done = true;
+                Some((Err(e), state))
+            }
+        }
+    })
+}
+
+impl State {
+    fn header(&self) -> Bytes {
+        match self.format {
+            Format::Csv => Bytes::from_static(CSV_HEADER.as_bytes()),
+            Format::Json => Bytes::from_static(b"["),
+            Format::Markdown => Bytes::from(format!("# Feedback {} to {}\n\n", self.from, self.to)),
+            Format::Zip => Bytes::new(),
+        }
+    }
+
+    async fn next_day(&mut self) -> io::Result<Option<Bytes>> {
+        while let Some(date) = self.days.pop_front() {
+            let Some(content) = self.storage.read_day(date).await? else {
+                // Removed since it was listed
+                continue;
+            };
+            debug!(%date, format = ?self.format, "Exporting day");
+
+            let chunk = match self.format {
+                Format::Zip => self.zip.add(&date.file_name(), date, content.as_bytes())?,
+                format => {
+                    let entries = feedback::parse_day(date, &content);
+                    let mut chunk = vec![];
+                    if format == Format::Markdown && !entries.is_empty() {
+                        chunk.extend_from_slice(format!("## {date}\n\n").as_bytes());
+                    }
+                    for entry in &entries {
+                        self.write_entry(&mut chunk, entry)?;
+                    }
+                    chunk
+                }
+            };
+
+            return Ok(Some(Bytes::from(chunk)));
+        }
+
+        Ok(None)
+    }
+
+    fn write_entry(&mut self, out: &mut Vec<u8>, entry: &Entry) -> io::Result<()> {
+        let timestamp = entry.timestamp.map(|time| time.to_rfc3339()).unwrap_or_default();
+
+        match self.format {
+            Format::Csv => {
+                let fields = [entry.id.clone(), entry.date.to_string(), timestamp, entry.body.clone(), metadata(entry)];
+                let row = fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",");
+                out.extend_from_slice(row.as_bytes());
+                out.extend_from_slice(b"\r\n");
+            }
+            Format::Json => {
+                if self.entries_written > 0 {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut *out, entry)?;
+            }
+            Format::Markdown => {
+                let time = entry.timestamp.map(|time| time.format("%H:%M:%S").to_string());
+                out.extend_from_slice(format!("### {}\n\n", time.as_deref().unwrap_or(&entry.id)).as_bytes());
+                for line in entry.body.lines() {
+                    out.extend_from_slice(format!("> {line}\n").as_bytes());
+                }
+                if !entry.metadata.is_empty() {
+                    out.extend_from_slice(format!("\n_{}_\n", metadata(entry)).as_bytes());
+                }
+                out.push(b'\n');
+            }
+            Format::Zip => unreachable!("ZIP exports contain the day files"),
+        }
+
+        self.entries_written += 1;
+        Ok(())
+    }
+
+    fn footer(&mut self) -> Bytes {
+        match self.format {
+            Format::Json => Bytes::from_static(b"]"),
+            Format::Markdown if self.entries_written == 0 => Bytes::from_static(b"No feedback in this range.\n"),
+            Format::Zip => Bytes::from(self.zip.finish()),
+            Format::Csv | Format::Markdown => Bytes::new(),
+        }
+    }
+}
+
+impl ZipDirectory {
+    /// A stored (uncompressed) file, the whole content is known so no data descriptor is needed
+    fn add(&mut self, name: &str, date: DayDate, content: &[u8]) -> io::Result<Vec<u8>> {
+        let too_large = || io::Error::other(format!("{name} is too large for a ZIP archive"));
+        let size = u32::try_from(content.len()).map_err(|_| too_large())?;
+        let offset = u32::try_from(self.offset).map_err(|_| too_large())?;
+        let name_len = u16::try_from(name.len()).map_err(|_| too_large())?;
+        self.count = self.count.checked_add(1).ok_or_else(too_large)?;
+
+        let crc = crc32fast::hash(content);
+        let date = date.as_naive();
+        // MS-DOS date, the day files don't need a time
+        let dos_date = (((date.year() - 1980).max(0) as u16) << 9) | ((date.month() as u16) << 5) | date.day() as u16;
+
+        let mut local = Vec::with_capacity(30 + name.len() + content.len());
+        local.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        local.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        local.extend_from_slice(&0u16.to_le_bytes()); // flags
+        local.extend_from_slice(&0u16.to_le_bytes()); // stored
+        local.extend_from_slice(&0u16.to_le_bytes()); // time
+        local.extend_from_slice(&dos_date.to_le_bytes());
+        local.extend_from_slice(&crc.to_le_bytes());
+        local.extend_from_slice(&size.to_le_bytes()); // compressed
+        local.extend_from_slice(&size.to_le_bytes()); // uncompressed
+        local.extend_from_slice(&name_len.to_le_bytes());
+        local.extend_from_slice(&0u16.to_le_bytes()); // extra
+        local.extend_from_slice(name.as_bytes());
+        local.extend_from_slice(content);
+
+        let records = &mut self.records;
+        records.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        records.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        records.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        records.extend_from_slice(&0u16.to_le_bytes()); // flags
+        records.extend_from_slice(&0u16.to_le_bytes()); // stored
+        records.extend_from_slice(&0u16.to_le_bytes()); // time
+        records.extend_from_slice(&dos_date.to_le_bytes());
+        records.extend_from_slice(&crc.to_le_bytes());
+        records.extend_from_slice(&size.to_le_bytes());
+        records.extend_from_slice(&size.to_le_bytes());
+        records.extend_from_slice(&name_len.to_le_bytes());
+        records.extend_from_slice(&[0; 8]); // extra, comment, disk, internal attributes
+        records.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        records.extend_from_slice(&offset.to_le_bytes());
+        records.extend_from_slice(name.as_bytes());
+
+        self.offset += local.len() as u64;
+        Ok(local)
+    }
+
+    fn finish(&mut self) -> Vec<u8> {
+        let mut end = std::mem::take(&mut self.records);
+        let directory_size = end.len() as u32;
+
+        end.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        end.extend_from_slice(&[0; 4]); // disk numbers
+        end.extend_from_slice(&self.count.to_le_bytes());
+        end.extend_from_slice(&self.count.to_le_bytes());
+        end.extend_from_slice(&directory_size.to_le_bytes());
+        end.extend_from_slice(&(self.offset as u32).to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // comment
+        end
+    }
+}
+
+/// `redacted=email,phone; other=x`
+fn metadata(entry: &Entry) -> String {
+    entry.metadata
+         .iter()
+         .map(|(key, values)| format!("{key}={}", values.join(",")))
+         .collect::<Vec<_>>()
+         .join("; ")
+}
+
+/// Quotes the field if needed and keeps spreadsheets from running it as a formula
+fn csv_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    };
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{app, fetch, temp_root};
+    use axum::http::StatusCode;
+
+    #[test]
+    fn escapes_csv_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\"\nbye"), "\"say \"\"hi\"\"\nbye\"");
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("-1"), "'-1");
+    }
+
+    #[test]
+    fn writes_a_valid_zip_directory() {
+        let mut zip = ZipDirectory::default();
+        let date = "2024-05-01".parse().unwrap();
+        let first = zip.add("a.txt", date, b"hello").unwrap();
+        let second = zip.add("b.txt", date, b"world!").unwrap();
+        let end = zip.finish();
+
+        assert_eq!(&first[..4], b"PK\x03\x04");
+        assert_eq!(first.len(), 30 + 5 + 5);
+        assert_eq!(zip.offset, (first.len() + second.len()) as u64);
+
+        // End of central directory: two entries, directory right after the files
+        let eocd = &end[end.len() - 22..];
+        assert_eq!(&eocd[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([eocd[10], eocd[11]]), 2);
+        assert_eq!(u32::from_le_bytes(eocd[16..20].try_into().unwrap()), zip.offset as u32);
+        assert_eq!(u32::from_le_bytes(eocd[12..16].try_into().unwrap()) as usize, end.len() - 22);
+    }
+
+    #[tokio::test]
+    async fn exports_ranges() {
+        let root = temp_root("export");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-05-01{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-01 - 12:00:00]z\n[meta redacted=email]\nHello, \"world\"\n{dashes}\n\n"),
+        ).unwrap();
+
+        let (status, body) = fetch(app(&root), "/export?from=2024-05-01&to=2024-05-02&format=csv").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            "id,date,timestamp,body,metadata\r\n2024-05-01.0,2024-05-01,2024-05-01T12:00:00+00:00,\"Hello, \"\"world\"\"\",redacted=email\r\n",
+        );
+
+        let (_, body) = fetch(app(&root), "/export?from=2024-05-01&to=2024-05-01&format=json").await;
+        let entries: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(entries[0]["id"], "2024-05-01.0");
+
+        let (_, body) = fetch(app(&root), "/export?from=2024-05-01&to=2024-05-01&format=md").await;
+        assert!(body.starts_with("# Feedback 2024-05-01 to 2024-05-01\n\n## 2024-05-01\n\n### 12:00:00\n\n> Hello"));
+
+        let (status, _) = fetch(app(&root), "/export?from=2024-05-01&to=2024-05-01&format=xls").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+use crate::storage::DayDate;
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Written by feedback_backend around every entry
+const DASH_CNT: usize = 50;
+const TIME_FORMAT: &str = "[%Y-%m-%d - %H:%M:%S]z";
+const META_PREFIX: &str = "[meta ";
+/// Marks the tombstone of a deleted entry, e.g. `[meta deleted=2024-05-02T10:00:00Z]`
+const DELETED_KEY: &str = "deleted";
+
+/// A single feedback entry of a day file.
+/// `id` is `{date}.{index}`, the index counts the entries of the day starting at 0.
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub id: String,
+    pub date: DayDate,
+    pub index: usize,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub body: String,
+    /// e.g. `{"redacted": ["email", "phone"]}` from `[meta redacted=email,phone]`
+    pub metadata: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DaySummary {
+    pub date: DayDate,
+    pub count: usize,
+    pub last_entry: Option<DateTime<Utc>>,
+}
+
+impl Entry {
+    fn is_deleted(&self) -> bool {
+        self.metadata.contains_key(DELETED_KEY)
+    }
+}
+
+impl DaySummary {
+    pub fn from_entries(date: DayDate, entries: &[Entry]) -> Self {
+        Self {
+            date,
+            count: entries.len(),
+            last_entry: entries.iter().filter_map(|entry| entry.timestamp).max(),
+        }
+    }
+}
+
+/// Parses a day file written by feedback_backend:
+/// dashes, the time, an optional metadata line, the body and closing dashes.
+/// Deleted entries are left out, their tombstones still count for the indices.
+pub fn parse_day(date: DayDate, content: &str) -> Vec<Entry> {
+    let separator = "-".repeat(DASH_CNT);
+    let mut entries = vec![];
+    let mut index = 0;
+    let mut block: Option<Vec<&str>> = None;
+
+    for line in content.lines() {
+        if line != separator {
+            if let Some(lines) = block.as_mut() {
+                lines.push(line);
+            }
+            continue;
+        }
+
+        match block.take() {
+            Some(lines) => {
+                let entry = parse_entry(date, index, &lines);
+                if !entry.is_deleted() {
+                    entries.push(entry);
+                }
+                index += 1;
+            }
+            None => block = Some(vec![]),
+        }
+    }
+
+    entries
+}
+
+/// The day file with entry `index` replaced by a tombstone: the time is kept, the metadata
+/// and body are replaced by `[meta deleted=<at>]`, so the indices of later entries don't change.
+/// `None` if there is no such entry or it was deleted already.
+pub fn tombstone(date: DayDate, content: &str, index: usize, at: DateTime<Utc>) -> Option<String> {
+    let separator = "-".repeat(DASH_CNT);
+    let marker = format!("{META_PREFIX}{DELETED_KEY}={}]", at.to_rfc3339_opts(SecondsFormat::Secs, true));
+    let mut lines = vec![];
+    let mut current = 0;
+    let mut replaced = false;
+    let mut block: Option<Vec<&str>> = None;
+
+    for line in content.lines() {
+        if line != separator {
+            match block.as_mut() {
+                Some(block) => block.push(line),
+                None => lines.push(line),
+            }
+            continue;
+        }
+
+        match block.take() {
+            Some(block) => {
+                let entry = parse_entry(date, current, &block);
+                if current == index && !entry.is_deleted() {
+                    lines.extend(block.first().filter(|_| entry.timestamp.is_some()));
+                    lines.push(&marker);
+                    replaced = true;
+                } else {
+                    lines.extend(block);
+                }
+                lines.push(line);
+                current += 1;
+            }
+            None => {
+                lines.push(line);
+                block = Some(vec![]);
+            }
+        }
+    }
+    // An unterminated block at the end is kept as it is
+    lines.extend(block.into_iter().flatten());
+
+    if !replaced {
+        return None;
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    Some(content)
+}
+
+fn parse_entry(date: DayDate, index: usize, lines: &[&str]) -> Entry {
+    let mut lines = lines;
+
+    let timestamp = lines.first()
+                         .and_then(|line| NaiveDateTime::parse_from_str(line, TIME_FORMAT).ok())
+                         .map(|time| time.and_utc());
+    if timestamp.is_some() {
+        lines = &lines[1..];
+    }
+
+    let mut metadata = BTreeMap::new();
+    if let Some(meta) = lines.first()
+                             .and_then(|line| line.strip_prefix(META_PREFIX))
+                             .and_then(|meta| meta.strip_suffix(']')) {
+        for (key, values) in meta.split_whitespace().filter_map(|pair| pair.split_once('=')) {
+            metadata.insert(
+                key.to_string(),
+                values.split(',').filter(|value| !value.is_empty()).map(String::from).collect(),
+            );
+        }
+        lines = &lines[1..];
+    }
+
+    Entry {
+        id: format!("{date}.{index}"),
+        date,
+        index,
+        timestamp,
+        body: lines.iter().map(|line| unescape(line)).collect::<Vec<_>>().join("\n"),
+        metadata,
+    }
+}
+
+/// feedback_backend adds a backslash to submitted lines that would read as metadata or a separator
+fn unescape(line: &str) -> &str {
+    let unescaped = line.trim_start_matches('\\');
+    let reserved = unescaped.starts_with(META_PREFIX) || unescaped == "-".repeat(DASH_CNT);
+    match line.strip_prefix('\\') {
+        Some(stripped) if reserved => stripped,
+        _ => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entries() {
+        let dashes = "-".repeat(DASH_CNT);
+        let content = format!(
+            "{dashes}\n[2024-05-01 - 08:00:00]z\nfirst\nsecond line\n{dashes}\n\n\
+             {dashes}\n[2024-05-01 - 17:30:05]z\n[meta redacted=email,phone]\nsecond\n{dashes}\n\n\
+             {dashes}\nno time\n{dashes}\n\n"
+        );
+        let date = "2024-05-01".parse().unwrap();
+
+        let entries = parse_day(date, &content);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].id, "2024-05-01.0");
+        assert_eq!(entries[0].body, "first\nsecond line");
+        assert!(entries[0].metadata.is_empty());
+        assert_eq!(entries[1].metadata["redacted"], ["email", "phone"]);
+        assert_eq!(entries[1].body, "second");
+        assert_eq!(entries[2].timestamp, None);
+        assert_eq!(entries[2].body, "no time");
+
+        let summary = DaySummary::from_entries(date, &entries);
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.last_entry.map(|time| time.to_rfc3339()).as_deref(), Some("2024-05-01T17:30:05+00:00"));
+    }
+
+    #[test]
+    fn tombstones_entries() {
+        let dashes = "-".repeat(DASH_CNT);
+        let content = format!(
+            "{dashes}\n[2024-05-01 - 08:00:00]z\n[meta redacted=email]\nfirst\n{dashes}\n\n\
+             {dashes}\n[2024-05-01 - 09:00:00]z\nsecond\n{dashes}\n\n"
+        );
+        let date = "2024-05-01".parse().unwrap();
+        let at = "2024-05-02T10:00:00Z".parse().unwrap();
+
+        let deleted = tombstone(date, &content, 0, at).unwrap();
+        assert_eq!(deleted, format!(
+            "{dashes}\n[2024-05-01 - 08:00:00]z\n[meta deleted=2024-05-02T10:00:00Z]\n{dashes}\n\n\
+             {dashes}\n[2024-05-01 - 09:00:00]z\nsecond\n{dashes}\n\n"
+        ));
+
+        let entries = parse_day(date, &deleted);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, "2024-05-01.1");
+        assert_eq!(DaySummary::from_entries(date, &entries).count, 1);
+
+        assert_eq!(tombstone(date, &deleted, 0, at), None);
+        assert_eq!(tombstone(date, &deleted, 2, at), None);
+    }
+
+    #[test]
+    fn unescapes_submitted_metadata() {
+        let date = "2024-05-01".parse().unwrap();
+        let dashes = "-".repeat(DASH_CNT);
+        let content = format!(
+            "{dashes}\n[2024-05-01 - 08:00:00]z\n\\[meta deleted=2024-05-01T09:00:00Z]\n\\{dashes}\n\\\\[meta x=y]\n\\path\n{dashes}\n\n"
+        );
+
+        let entries = parse_day(date, &content);
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].metadata.is_empty());
+        assert_eq!(entries[0].body, format!("[meta deleted=2024-05-01T09:00:00Z]\n{dashes}\n\\[meta x=y]\n\\path"));
+    }
+}
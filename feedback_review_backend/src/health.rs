@@ -0,0 +1,24 @@
+use crate::FILE_ROOT;
+use axum::response::IntoResponse;
+use feedback_telemetry::health::{readiness, Service};
+use tokio::fs;
+
+pub use feedback_telemetry::health::{check, healthz};
+
+const SERVICE: Service = Service {
+    name: env!("CARGO_PKG_NAME"),
+    version: env!("CARGO_PKG_VERSION"),
+    // Day files are only rewritten to delete entries
+    storage_mode: "read-write",
+    storage_root: FILE_ROOT,
+};
+
+pub async fn readyz() -> impl IntoResponse {
+    // Writing only happens on deletions, so being able to list the feedback is all we need
+    let result = fs::read_dir(FILE_ROOT).await.map(|_| ());
+    readiness(result, FILE_ROOT, "readable")
+}
+
+pub async fn version() -> impl IntoResponse {
+    feedback_telemetry::health::version(SERVICE)
+}
@@ -1,22 +1,63 @@
+mod access;
+mod annotations;
+mod audit;
+mod auth;
+mod cache;
+mod days;
+mod deletion;
+mod events;
+mod export;
+mod feedback;
+mod health;
+mod metrics;
+mod range;
+mod search;
+mod stats;
+mod storage;
+mod store;
+#[cfg(test)]
+mod test_support;
+
+use std::collections::HashSet;
 use std::env;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 use anyhow::{Context, Result};
-use axum::http::{header, HeaderValue, Method, StatusCode};
-use axum::response::IntoResponse;
+use axum::body::Body;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::{Json, Router};
-use axum::extract::Path;
-use axum::routing::get;
-use serde::Serialize;
-use tokio::fs;
+use axum::extract::{FromRef, Query, State};
+use axum::middleware;
+use axum::routing::{delete, get, post, put};
+use feedback_telemetry::cors::{self, CorsPolicy};
+use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
-use tower_http::cors::CorsLayer;
-use tracing::{debug, error, info, subscriber, Level};
-use tracing_subscriber::FmtSubscriber;
+use tracing::{debug, error, info, Level};
+use crate::access::AccessLog;
+use crate::audit::AuditLog;
+use crate::auth::{Auth, Permission};
+use crate::cache::Validators;
+use crate::range::EntryCursor;
+use crate::annotations::Annotations;
+use crate::days::DayIndex;
+use crate::search::{Filter, SearchIndex};
+use crate::stats::StatsCache;
+use crate::export::Format;
+use crate::feedback::{DaySummary, Entry};
+use crate::storage::{DayDate, InvalidInput, Storage};
 
 const FILE_ROOT: &str = "/feedback/";
 const FILE_SUFFIX: &str = "-feedback.txt";
+/// Everything the review backend writes itself, it only writes to the feedback to delete entries
+const DATA_ROOT: &str = "/review_data/";
 const PORT: u16 = 8080; // This only runs in docker, so 8080 works
+const DEFAULT_PAGE_SIZE: usize = 30;
+const MAX_PAGE_SIZE: usize = 366;
+const DEFAULT_RANGE_LIMIT: usize = 100;
+const MAX_RANGE_LIMIT: usize = 1000;
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
 static LOG_LEVEL: LazyLock<Level> = LazyLock::new(|| {
     const ENV_KEY: &str = "LOG_LEVEL";
     #[cfg(debug_assertions)]
@@ -37,38 +78,194 @@ static LOG_LEVEL: LazyLock<Level> = LazyLock::new(|| {
     }
 });
 
+#[derive(Clone)]
+struct AppState {
+    auth: Arc<Auth>,
+    storage: Storage,
+    days: Arc<DayIndex>,
+    search: Arc<SearchIndex>,
+    stats: Arc<StatsCache>,
+    annotations: Arc<Annotations>,
+    audit: Arc<AuditLog>,
+    access: Arc<AccessLog>,
+}
+
+impl FromRef<AppState> for Arc<Auth> {
+    fn from_ref(state: &AppState) -> Self {
+        state.auth.clone()
+    }
+}
+
+impl FromRef<AppState> for Storage {
+    fn from_ref(state: &AppState) -> Self {
+        state.storage.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<DayIndex> {
+    fn from_ref(state: &AppState) -> Self {
+        state.days.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<SearchIndex> {
+    fn from_ref(state: &AppState) -> Self {
+        state.search.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<StatsCache> {
+    fn from_ref(state: &AppState) -> Self {
+        state.stats.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Annotations> {
+    fn from_ref(state: &AppState) -> Self {
+        state.annotations.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<AuditLog> {
+    fn from_ref(state: &AppState) -> Self {
+        state.audit.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<AccessLog> {
+    fn from_ref(state: &AppState) -> Self {
+        state.access.clone()
+    }
+}
+
+/// `cursor` is the `next_cursor` of the previous page, `from` and `to` are inclusive
+#[derive(Debug, Deserialize)]
+struct DatesQuery {
+    cursor: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DateBounds {
+    cursor: Option<DayDate>,
+    from: Option<DayDate>,
+    to: Option<DayDate>,
+}
+
+impl DatesQuery {
+    fn bounds(&self) -> Result<DateBounds, InvalidInput> {
+        Ok(DateBounds {
+            cursor: DayDate::parse_param(self.cursor.as_deref())?,
+            from: DayDate::parse_param(self.from.as_deref())?,
+            to: DayDate::parse_param(self.to.as_deref())?,
+        })
+    }
+}
+
+impl DateBounds {
+    /// Newest first, so the next page starts below the cursor
+    fn contains(&self, date: DayDate) -> bool {
+        self.cursor.is_none_or(|cursor| date < cursor)
+            && self.from.is_none_or(|from| date >= from)
+            && self.to.is_none_or(|to| date <= to)
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct FeedbackDates {
-    dates: Option<Vec<String>>,
+    dates: Option<Vec<DaySummary>>,
+    next_cursor: Option<DayDate>,
 }
 
+/// The raw day file, kept for clients that still parse it themselves
 #[derive(Debug, Serialize)]
 struct FeedbackResponse {
     feedback: Option<String>,
 }
 
+/// `from` and `to` are inclusive, `cursor` is the `next_cursor` of the previous page
+#[derive(Debug, Deserialize)]
+struct RangeQuery {
+    from: Option<String>,
+    to: Option<String>,
+    cursor: Option<String>,
+    limit: Option<String>,
+}
+
+/// `format` is one of `csv`, `json`, `md` or `zip`
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    from: Option<String>,
+    to: Option<String>,
+    format: Option<String>,
+}
+
+/// `q` supports `"quoted phrases"`, every term and phrase has to match
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<String>,
+    offset: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntriesQuery {
+    tag: Option<String>,
+}
+
+/// Both bounds are optional and inclusive
+#[derive(Debug, Deserialize)]
+struct StatsQuery {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct EntriesError {
+    error: &'static str,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    subscriber::set_global_default(
-        FmtSubscriber::builder()
-            .with_max_level(*LOG_LEVEL)
-            .finish()
-    ).with_context(|| format!("Failed to set up logging with level {}", *LOG_LEVEL))?;
-
-    let cors = CorsLayer::new()
-        .allow_origin(
-            env::var("ALLOW_ORIGIN")
-                .context("ALLOW_ORIGIN env var is not set")?
-                .parse::<HeaderValue>()
-                .context("Failed to parse origin")?
-        )
-        .allow_methods([Method::POST, Method::OPTIONS])
-        .allow_headers([header::CONTENT_TYPE]);
-
-    let app = Router::new()
-        .route("/dates", get(get_available_feedbacks))
-        .route("/feedback/{date}", get(get_feedback_for_date))
-        .layer(cors);
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("healthcheck") => return health::check(PORT).await,
+        Some("token" | "user") => return auth::cli(&args).await,
+        _ => {}
+    }
+
+    let _telemetry = feedback_telemetry::init(*LOG_LEVEL, env!("CARGO_PKG_NAME"))?;
+
+    let cors = CorsPolicy::from_env()?;
+    let storage = Storage::default();
+    let days = Arc::new(DayIndex::new(storage.clone()));
+    let loaded = days.days().await.with_context(|| format!("Failed to load the day files from {FILE_ROOT}"))?;
+    info!("Loaded {} days", loaded.len());
+    days.watch();
+
+    let state = AppState {
+        auth: Arc::new(Auth::new()),
+        search: Arc::new(SearchIndex::new(days.clone())),
+        stats: Arc::new(StatsCache::new(days.clone())),
+        days,
+        annotations: Arc::new(Annotations::default()),
+        audit: Arc::new(AuditLog::default()),
+        access: Arc::new(AccessLog::default()),
+        storage,
+    };
+
+    let app = router(cors, state);
+
+    // Registers the metrics, so they are listed before anything was counted
+    LazyLock::force(&metrics::METRICS);
+    let app = feedback_telemetry::metrics::mount(app).await?;
+    let app = feedback_telemetry::trace_requests(app);
 
     let listener = TcpListener::bind(
         SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), PORT)
@@ -85,46 +282,441 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn get_available_feedbacks() -> impl IntoResponse {
-    debug!("Getting available feedbacks");
+/// Every route with its permission, the auth layers only wrap the routes added before them
+fn router(cors: CorsPolicy, state: AppState) -> Router {
+    // Sessions are cookies, the browser only sends them cross origin with credentials
+    cors.allow_headers(&[header::AUTHORIZATION])
+        .allow_credentials(true)
+        .router()
+        .route("/dates", cors::GET, auth::require(Permission::Read, get(get_available_feedbacks)))
+        .route("/feedback", cors::GET, auth::require(Permission::Read, get(get_feedback_range)))
+        .route("/feedback/{date}", cors::GET, auth::require(Permission::Read, get(get_feedback_for_date)))
+        .route("/feedback/{date}/entries", cors::GET, auth::require(Permission::Read, get(get_entries_for_date)))
+        .route("/feedback/{date}/entries/{index}", cors::DELETE, auth::require(Permission::Delete, delete(deletion::delete_entry)))
+        .route("/feedback/{date}/annotations", cors::GET, auth::require(Permission::Read, get(annotations::get_annotations)))
+        .route("/feedback/{date}/entries/{index}/status", cors::PUT, auth::require(Permission::Moderate, put(annotations::set_status)))
+        .route("/feedback/{date}/entries/{index}/tags", cors::PUT, auth::require(Permission::Moderate, put(annotations::set_tags)))
+        .route("/feedback/{date}/entries/{index}/comments", cors::POST, auth::require(Permission::Moderate, post(annotations::add_comment)))
+        .route("/feedback/{date}/entries/{index}/comments/{comment}", cors::PUT, auth::require(Permission::Moderate, put(annotations::edit_comment)))
+        .route("/feedback/{date}/entries/{index}/assignment", cors::PUT, auth::require(Permission::Moderate, put(annotations::set_assignment)))
+        .route("/feedback/{date}/entries/{index}/wall", cors::PUT, auth::require(Permission::Present, put(annotations::set_wall)))
+        .route("/tags", cors::GET, auth::require(Permission::Read, get(annotations::get_tags)))
+        .route("/reviewers", cors::GET, auth::require(Permission::Moderate, get(auth::reviewers)))
+        .route("/me/assignments", cors::GET, auth::require(Permission::Read, get(annotations::my_assignments)))
+        .route("/events", cors::GET, auth::require(Permission::Read, get(events::get_events)))
+        .route("/search", cors::GET, auth::require(Permission::Read, get(search_feedback)))
+        .route("/stats", cors::GET, auth::require(Permission::Read, get(get_stats)))
+        .route("/export", cors::GET, auth::require(Permission::Export, get(export_feedback)))
+        .route("/audit/access", cors::GET, auth::require(Permission::Audit, get(access::get_access_log)))
+        .get("/me", auth::me)
+        .route_layer(middleware::from_fn_with_state(state.access.clone(), access::track))
+        .route_layer(middleware::from_fn_with_state(state.auth.clone(), auth::require_auth))
+        .post("/login", auth::login)
+        .post("/logout", auth::logout)
+        .get("/version", health::version)
+        .into_router()
+        .with_state(state)
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route_layer(middleware::from_fn(metrics::track_requests))
+}
+
+/// With `tag`, only days with a tagged entry are listed and only those entries are counted
+async fn get_available_feedbacks(
+    State(days): State<Arc<DayIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    Query(query): Query<DatesQuery>,
+) -> Response {
+    debug!(?query, "Getting available feedbacks");
+
+    let bounds = match query.bounds() {
+        Ok(bounds) => bounds,
+        Err(e) => return e.into_response(),
+    };
+
+    let limit = match parse_limit(query.limit.as_deref(), DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE) {
+        Ok(limit) => limit,
+        Err(e) => return e.into_response(),
+    };
+
+    let listed = match days.days().await {
+        Ok(listed) => listed,
+        Err(e) => {
+            error!("Failed to list day files: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(FeedbackDates { dates: None, next_cursor: None }))
+                .into_response();
+        }
+    };
+
+    let tagged = match annotations::tag_filter(&annotations, query.tag.as_deref()).await {
+        Ok(tagged) => tagged,
+        Err(e) => return e,
+    };
+    let tagged_days = tagged.as_ref().map(|tagged| tagged.iter().map(|(date, _)| *date).collect::<HashSet<_>>());
 
-    let mut dates = vec![];
+    let mut page = listed.into_iter()
+                       .filter(|date| bounds.contains(*date))
+                       .filter(|date| tagged_days.as_ref().is_none_or(|days| days.contains(date)))
+                       .take(limit + 1)
+                       .collect::<Vec<_>>();
+    let next_cursor = if page.len() > limit {
+        page.truncate(limit);
+        page.last().copied()
+    } else {
+        None
+    };
 
-    match fs::read_dir(FILE_ROOT).await {
-        Ok(mut dir) => {
-            while let Ok(Some(dir)) = dir.next_entry().await {
-                if let Ok(file_name) = dir.file_name().into_string() {
-                    dates.push(file_name);
-                } else {
-                    error!("Failed to convert file name to string");
-                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(FeedbackDates { dates: None }));
+    let mut dates = Vec::with_capacity(page.len());
+    for date in page {
+        match days.day(date).await {
+            Ok(Some(day)) => match &tagged {
+                Some(tagged) => {
+                    let entries = day.entries
+                                     .iter()
+                                     .filter(|entry| tagged.contains(&(date, entry.index)))
+                                     .cloned()
+                                     .collect::<Vec<_>>();
+                    dates.push(DaySummary::from_entries(date, &entries));
                 }
+                None => dates.push(DaySummary::from_entries(date, &day.entries)),
+            },
+            // Removed since it was listed
+            Ok(None) => {}
+            Err(e) => {
+                error!("Failed to read day {date}: {e}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(FeedbackDates { dates: None, next_cursor: None }))
+                    .into_response();
             }
         }
+    }
+
+    debug!(dates = dates.len(), ?next_cursor);
+
+    (StatusCode::OK, Json(FeedbackDates { dates: Some(dates), next_cursor })).into_response()
+}
+
+fn parse_limit(limit: Option<&str>, default: usize, max: usize) -> Result<usize, InvalidInput> {
+    match limit.map(str::parse) {
+        None => Ok(default),
+        Some(Ok(limit)) if (1..=max).contains(&limit) => Ok(limit),
+        Some(_) => Err(InvalidInput::new(
+            "invalid_limit",
+            format!("Expected a number from 1 to {max}"),
+            limit.unwrap_or_default(),
+        )),
+    }
+}
+
+async fn get_feedback_for_date(State(storage): State<Storage>, date: DayDate, headers: HeaderMap) -> Response {
+    debug!(%date);
+    let validators = match day_validators(&storage, date, &headers).await {
+        Ok(validators) => validators,
+        Err(response) => return response,
+    };
+
+    match storage.read_day(date).await {
+        Ok(Some(feedback)) => cache::with_validators(validators, Json(FeedbackResponse { feedback: Some(feedback) }).into_response()),
+        Ok(None) => {
+            error!("No feedback for date {date}");
+            (StatusCode::NOT_FOUND, Json(FeedbackResponse { feedback: None })).into_response()
+        }
         Err(e) => {
-            error!("Failed to read directory {FILE_ROOT}: {e}");
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(FeedbackDates { dates: None }));
+            error!("Failed to read feedback for date {date}: {e}");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(FeedbackResponse { feedback: None })).into_response()
         }
     }
+}
+
+/// The validators of a day file, `None` if there is no file.
+/// Responds early with 304 if the client's copy is current.
+async fn day_validators(storage: &Storage, date: DayDate, headers: &HeaderMap) -> Result<Option<Validators>, Response> {
+    match storage.day_metadata(date).await {
+        Ok(Some(meta)) => {
+            let validators = Validators::of(date, (meta.modified().ok(), meta.len()));
+            if validators.fresh(headers) {
+                debug!(%date, "Not modified");
+                return Err(validators.not_modified());
+            }
+            Ok(Some(validators))
+        }
+        Ok(None) => Ok(None),
+        Err(e) => {
+            error!("Failed to read metadata of day {date}: {e}");
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response())
+        }
+    }
+}
+
+async fn get_entries_for_date(
+    State(days): State<Arc<DayIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    date: DayDate,
+    Query(query): Query<EntriesQuery>,
+    headers: HeaderMap,
+) -> Response {
+    debug!(%date, ?query, "Getting entries");
+
+    let tagged = match annotations::tag_filter(&annotations, query.tag.as_deref()).await {
+        Ok(tagged) => tagged,
+        Err(e) => return e,
+    };
+    match days.day(date).await {
+        Ok(Some(day)) => {
+            // Tags change without the day file changing, so filtered entries aren't cached.
+            // The validators describe the loaded entries, not whatever the file holds right now.
+            let validators = tagged.is_none().then(|| Validators::of(date, day.state));
+            if let Some(validators) = &validators && validators.fresh(&headers) {
+                debug!(%date, "Not modified");
+                return validators.not_modified();
+            }
+            let response = Json::<Vec<&Entry>>(
+                day.entries
+                   .iter()
+                   .filter(|entry| tagged.as_ref().is_none_or(|tagged| tagged.contains(&(date, entry.index))))
+                   .collect(),
+            ).into_response();
+            cache::with_validators(validators, response)
+        }
+        Ok(None) => (StatusCode::NOT_FOUND, Json(EntriesError { error: "not_found" })).into_response(),
+        Err(e) => {
+            error!("Failed to read feedback for date {date}: {e}");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response()
+        }
+    }
+}
+
+async fn get_feedback_range(State(index): State<Arc<DayIndex>>, Query(query): Query<RangeQuery>) -> Response {
+    debug!(?query, "Getting feedback range");
+
+    let (from, to) = match parse_range(query.from.as_deref(), query.to.as_deref()) {
+        Ok(range) => range,
+        Err(e) => return e.into_response(),
+    };
+    let limit = match parse_limit(query.limit.as_deref(), DEFAULT_RANGE_LIMIT, MAX_RANGE_LIMIT) {
+        Ok(limit) => limit,
+        Err(e) => return e.into_response(),
+    };
+    let cursor = match query.cursor.as_deref().map(EntryCursor::parse).transpose() {
+        Ok(cursor) => cursor,
+        Err(message) => return InvalidInput::new("invalid_cursor", message, query.cursor.unwrap_or_default())
+            .into_response(),
+    };
+
+    let days = match days_between(&index, from, to).await {
+        Ok(days) => days,
+        Err(e) => return e,
+    };
+
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        Body::from_stream(range::stream_entries(index, days, cursor, limit)),
+    ).into_response()
+}
+
+async fn export_feedback(State(storage): State<Storage>, State(index): State<Arc<DayIndex>>, Query(query): Query<ExportQuery>) -> Response {
+    debug!(?query, "Exporting feedback");
+
+    let (from, to) = match parse_range(query.from.as_deref(), query.to.as_deref()) {
+        Ok(range) => range,
+        Err(e) => return e.into_response(),
+    };
+    let format = match query.format.as_deref().unwrap_or("csv").parse::<Format>() {
+        Ok(format) => format,
+        Err(message) => return InvalidInput::new("invalid_format", message, query.format.unwrap_or_default())
+            .into_response(),
+    };
+
+    let days = match days_between(&index, from, to).await {
+        Ok(days) => days,
+        Err(e) => return e,
+    };
+
+    let file_name = format!("feedback-{from}-{to}.{}", format.extension());
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{file_name}\"")),
+        ],
+        Body::from_stream(export::stream_export(storage, format, from, to, days)),
+    ).into_response()
+}
+
+/// Both bounds are required and inclusive
+fn parse_range(from: Option<&str>, to: Option<&str>) -> Result<(DayDate, DayDate), InvalidInput> {
+    match (DayDate::parse_param(from)?, DayDate::parse_param(to)?) {
+        (Some(from), Some(to)) if from <= to => Ok((from, to)),
+        (Some(from), Some(to)) => Err(InvalidInput::new("invalid_range", "from has to be before to", format!("{from}..{to}"))),
+        _ => Err(InvalidInput::date("Both from and to are required", "")),
+    }
+}
+
+async fn days_between(index: &DayIndex, from: DayDate, to: DayDate) -> Result<Vec<DayDate>, Response> {
+    match index.days().await {
+        Ok(days) => Ok(days.into_iter()
+                           .filter(|date| (from..=to).contains(date))
+                           .collect()),
+        Err(e) => {
+            error!("Failed to list day files: {e}");
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response())
+        }
+    }
+}
+
+async fn search_feedback(
+    State(search): State<Arc<SearchIndex>>,
+    State(annotations): State<Arc<Annotations>>,
+    Query(query): Query<SearchQuery>,
+) -> Response {
+    debug!(?query, "Searching");
+
+    let q = query.q.as_deref().unwrap_or_default();
+    let clauses = search::parse_query(q);
+    if clauses.is_empty() {
+        return InvalidInput::new("invalid_query", "Expected at least one word to search for", q).into_response();
+    }
 
-    let dates = dates.into_iter()
-                     .map(|date| date.replace(FILE_SUFFIX, ""))
-                     .collect();
+    let filter = match (DayDate::parse_param(query.from.as_deref()), DayDate::parse_param(query.to.as_deref())) {
+        (Ok(from), Ok(to)) => Filter { from, to, entries: None },
+        (Err(e), _) | (_, Err(e)) => return e.into_response(),
+    };
+    let filter = match annotations::tag_filter(&annotations, query.tag.as_deref()).await {
+        Ok(entries) => Filter { entries, ..filter },
+        Err(e) => return e,
+    };
+    let limit = match parse_limit(query.limit.as_deref(), DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT) {
+        Ok(limit) => limit,
+        Err(e) => return e.into_response(),
+    };
+    let offset = match query.offset.as_deref().map(str::parse::<usize>) {
+        None => 0,
+        Some(Ok(offset)) => offset,
+        Some(Err(e)) => return InvalidInput::new("invalid_offset", e.to_string(), query.offset.unwrap_or_default())
+            .into_response(),
+    };
 
-    debug!(?dates);
+    if let Err(e) = search.refresh().await {
+        error!("Failed to update the search index: {e}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "index_failed" })).into_response();
+    }
 
-    (StatusCode::OK, Json(FeedbackDates { dates: Some(dates) }))
+    Json(search.search(&clauses, &filter, offset, limit).await).into_response()
 }
 
-async fn get_feedback_for_date(Path(date): Path<String>) -> impl IntoResponse {
-    debug!(date);
-    let f_name = format!("{FILE_ROOT}{date}{FILE_SUFFIX}");
-    debug!("Checking for file: {f_name}");
-    let Ok(feedback) = fs::read_to_string(&f_name)
-        .await else {
-        error!("No feedback for date {date}");
-        return (StatusCode::NOT_FOUND, Json(FeedbackResponse { feedback: None }));
+async fn get_stats(
+    State(index): State<Arc<DayIndex>>,
+    State(stats): State<Arc<StatsCache>>,
+    Query(query): Query<StatsQuery>,
+) -> Response {
+    debug!(?query, "Computing statistics");
+
+    let (from, to) = match (DayDate::parse_param(query.from.as_deref()), DayDate::parse_param(query.to.as_deref())) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(e), _) | (_, Err(e)) => return e.into_response(),
+    };
+
+    let days = match index.days().await {
+        Ok(days) => days.into_iter()
+                        .filter(|date| from.is_none_or(|from| *date >= from) && to.is_none_or(|to| *date <= to))
+                        .collect::<Vec<_>>(),
+        Err(e) => {
+            error!("Failed to list day files: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response();
+        }
     };
 
-    (StatusCode::OK, Json(FeedbackResponse { feedback: Some(feedback) }))
+    match stats.stats(&days).await {
+        Ok(stats) => Json(stats).into_response(),
+        Err(e) => {
+            error!("Failed to compute statistics: {e}");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(EntriesError { error: "read_failed" })).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{app, fetch, temp_root};
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn serves_existing_day() {
+        let root = temp_root("existing");
+        std::fs::write(root.join(format!("2024-05-01{FILE_SUFFIX}")), "hello").unwrap();
+
+        let (status, body) = fetch(app(&root), "/feedback/2024-05-01").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("hello"));
+
+        let (status, _) = fetch(app(&root), "/feedback/2024-05-02").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, body) = fetch(app(&root), "/feedback/2024-05-01/entries").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "[]");
+
+        let (status, _) = fetch(app(&root), "/feedback/..%2Fsecret/entries").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_traversal_attempts() {
+        let root = temp_root("traversal").join("feedback");
+        std::fs::create_dir_all(&root).unwrap();
+        // Would be found if the date was interpolated into the path
+        std::fs::write(root.parent().unwrap().join(format!("secret{FILE_SUFFIX}")), "secret").unwrap();
+
+        for uri in [
+            "/feedback/..%2Fsecret",
+            "/feedback/..%2F..%2F..%2Fetc%2Fpasswd",
+            "/feedback/%2E%2E%2Fsecret",
+            "/feedback/2024-05-01%2F..%2F..%2Fsecret",
+            "/feedback/..%5Csecret",
+            "/feedback/2024-05-01%00",
+            "/feedback/2024-5-1",
+        ] {
+            let (status, body) = fetch(app(&root), uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+            assert!(body.contains("invalid_date"), "{uri}: {body}");
+        }
+
+        std::fs::remove_dir_all(root.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn lists_only_day_files_newest_first() {
+        let root = temp_root("dates");
+        for date in ["2024-05-01", "2024-05-03", "2024-04-30", "2024-05-02"] {
+            let entry = format!("{dashes}\n[{date} - 12:00:00]z\nhi\n{dashes}\n\n", dashes = "-".repeat(50));
+            std::fs::write(root.join(format!("{date}{FILE_SUFFIX}")), entry.repeat(2)).unwrap();
+        }
+        std::fs::write(root.join(".readyz"), "").unwrap();
+        std::fs::write(root.join(format!("notes{FILE_SUFFIX}")), "").unwrap();
+
+        let (status, body) = fetch(app(&root), "/dates?limit=2").await;
+        assert_eq!(status, StatusCode::OK);
+        let page: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(page["dates"][0]["date"], "2024-05-03");
+        assert_eq!(page["dates"][0]["count"], 2);
+        assert_eq!(page["dates"][0]["last_entry"], "2024-05-03T12:00:00Z");
+        assert_eq!(page["dates"][1]["date"], "2024-05-02");
+        assert_eq!(page["next_cursor"], "2024-05-02");
+
+        let (_, body) = fetch(app(&root), "/dates?limit=2&cursor=2024-05-02&from=2024-05-01").await;
+        let page: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(page["dates"].as_array().unwrap().len(), 1);
+        assert_eq!(page["dates"][0]["date"], "2024-05-01");
+        assert_eq!(page["next_cursor"], serde_json::Value::Null);
+
+        let (status, body) = fetch(app(&root), "/dates?to=..%2F").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("invalid_date"));
+
+        let (status, _) = fetch(app(&root), "/dates?limit=0").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
 }
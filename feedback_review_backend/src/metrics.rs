@@ -0,0 +1,41 @@
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use feedback_telemetry::metrics::REGISTRY;
+use prometheus::{IntCounterVec, Opts};
+use std::sync::LazyLock;
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    requests: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let requests = IntCounterVec::new(
+            Opts::new("review_requests_total", "Requests to the review API"),
+            &["route", "status"],
+        ).unwrap();
+
+        REGISTRY.register(Box::new(requests.clone())).unwrap();
+
+        Self { requests }
+    }
+}
+
+/// Counts every request by its route pattern (e.g. `/feedback/{date}`), never by the raw path
+pub async fn track_requests(req: Request, next: Next) -> Response {
+    let route = req.extensions()
+                   .get::<MatchedPath>()
+                   .map(|path| path.as_str().to_string())
+                   .unwrap_or_else(|| String::from("unmatched"));
+
+    let response = next.run(req).await;
+
+    METRICS.requests
+           .with_label_values(&[route.as_str(), response.status().as_str()])
+           .inc();
+
+    response
+}
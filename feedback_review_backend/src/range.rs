@@ -0,0 +1,202 @@
+use crate::days::DayIndex;
+use crate::feedback::Entry;
+use crate::storage::DayDate;
+use axum::body::Bytes;
+use futures_util::stream::{self, Stream};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+/// Where the previous page ended, the `id` of its last entry (`{date}.{index}`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryCursor {
+    date: DayDate,
+    index: usize,
+}
+
+#[derive(Serialize)]
+struct Trailer<'a> {
+    next_cursor: Option<&'a str>,
+}
+
+struct State {
+    index: Arc<DayIndex>,
+    /// Oldest first, only serialized when the previous day is done
+    days: VecDeque<DayDate>,
+    entries: VecDeque<Entry>,
+    cursor: Option<EntryCursor>,
+    remaining: usize,
+    last_id: Option<String>,
+    started: bool,
+    done: bool,
+}
+
+impl EntryCursor {
+    pub fn parse(cursor: &str) -> Result<Self, String> {
+        let (date, index) = cursor.rsplit_once('.')
+                                  .ok_or_else(|| String::from("Expected an entry id like 2024-05-01.3"))?;
+        Ok(Self {
+            date: date.parse()?,
+            index: index.parse().map_err(|e| format!("Invalid entry index: {e}"))?,
+        })
+    }
+
+    fn after(&self, entry: &Entry) -> bool {
+        (entry.date, entry.index) > (self.date, self.index)
+    }
+}
+
+/// Streams `{"entries":[...],"next_cursor":...}` for `days` in chronological order.
+/// Entries come from the [`DayIndex`], only a single day is serialized at a time.
+pub fn stream_entries(
+    index: Arc<DayIndex>,
+    mut days: Vec<DayDate>,
+    cursor: Option<EntryCursor>,
+    limit: usize,
+) -> impl Stream<Item = io::Result<Bytes>> {
+    days.sort_unstable();
+    days.retain(|date| cursor.is_none_or(|cursor| *date >= cursor.date));
+
+    let state = State {
+        index,
+        days: days.into(),
+        entries: VecDeque::new(),
+        cursor,
+        remaining: limit,
+        last_id: None,
+        started: false,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+
+        if !state.started {
+            state.started = true;
+            return Some((Ok(Bytes::from_static(b"{\"entries\":[")), state));
+        }
+
+        match state.next_chunk().await {
+            Ok(Some(chunk)) => Some((Ok(chunk), state)),
+            Ok(None) => {
+                state.done = true;
+                Some((Ok(state.trailer()), state))
+            }
+            Err(e) => {
+                // The status is already sent, aborting the body is all that's left
+                error!("Failed to stream entries: {e}");
+                state.done = true;
+                Some((Err(e), state))
+            }
+        }
+    })
+}
+
+impl State {
+    /// The next entries of the current day, reading the next day once it is used up
+    async fn next_chunk(&mut self) -> io::Result<Option<Bytes>> {
+        while self.remaining > 0 {
+            if self.entries.is_empty() {
+                let Some(date) = self.days.pop_front() else {
+                    return Ok(None);
+                };
+                let Some(day) = self.index.day(date).await? else {
+                    // Removed since it was listed
+                    continue;
+                };
+
+                debug!(%date, "Streaming day");
+                let cursor = self.cursor;
+                self.entries = day.entries
+                                  .iter()
+                                  .filter(|entry| cursor.is_none_or(|cursor| cursor.after(entry)))
+                                  .cloned()
+                                  .collect();
+                continue;
+            }
+
+            let take = self.remaining.min(self.entries.len());
+            let mut chunk = vec![];
+            for entry in self.entries.drain(..take) {
+                if self.last_id.is_some() {
+                    chunk.push(b',');
+                }
+                serde_json::to_writer(&mut chunk, &entry)?;
+                self.last_id = Some(entry.id);
+            }
+            self.remaining -= take;
+
+            return Ok(Some(Bytes::from(chunk)));
+        }
+
+        Ok(None)
+    }
+
+    /// Only links the next page if the limit cut it short
+    fn trailer(&self) -> Bytes {
+        let more = self.remaining == 0 && (!self.entries.is_empty() || !self.days.is_empty());
+        let trailer = Trailer { next_cursor: self.last_id.as_deref().filter(|_| more) };
+
+        let mut bytes = b"],".to_vec();
+        let json = serde_json::to_vec(&trailer).unwrap_or_else(|_| b"{}".to_vec());
+        // Merge `{"next_cursor":...}` into the open object
+        bytes.extend_from_slice(&json[1..]);
+        Bytes::from(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{app, fetch, temp_root};
+    use axum::http::StatusCode;
+
+    #[test]
+    fn parses_cursors() {
+        let cursor = EntryCursor::parse("2024-05-01.3").unwrap();
+        assert_eq!(cursor.date.to_string(), "2024-05-01");
+        assert_eq!(cursor.index, 3);
+
+        for cursor in ["2024-05-01", "2024-05-01.x", "../x.1", "2024-05-01.-1", ""] {
+            assert!(EntryCursor::parse(cursor).is_err(), "{cursor:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_ranges_in_pages() {
+        let root = temp_root("range");
+        let dashes = "-".repeat(50);
+        for (date, count) in [("2024-05-01", 2), ("2024-05-02", 0), ("2024-05-03", 3), ("2024-05-04", 1)] {
+            let content = (0..count)
+                .map(|i| format!("{dashes}\n[{date} - 12:00:0{i}]z\nentry {i}\n{dashes}\n\n"))
+                .collect::<String>();
+            std::fs::write(root.join(format!("{date}{FILE_SUFFIX}")), content).unwrap();
+        }
+
+        let mut ids = vec![];
+        let mut uri = String::from("/feedback?from=2024-05-01&to=2024-05-03&limit=2");
+        loop {
+            let (status, body) = fetch(app(&root), &uri).await;
+            assert_eq!(status, StatusCode::OK, "{body}");
+            let page: serde_json::Value = serde_json::from_str(&body).unwrap();
+            ids.extend(page["entries"].as_array().unwrap().iter().map(|entry| entry["id"].as_str().unwrap().to_string()));
+            match page["next_cursor"].as_str() {
+                Some(cursor) => uri = format!("/feedback?from=2024-05-01&to=2024-05-03&limit=2&cursor={cursor}"),
+                None => break,
+            }
+        }
+        assert_eq!(ids, ["2024-05-01.0", "2024-05-01.1", "2024-05-03.0", "2024-05-03.1", "2024-05-03.2"]);
+
+        for uri in ["/feedback?from=2024-05-03&to=2024-05-01", "/feedback?from=2024-05-01", "/feedback?from=2024-05-01&to=2024-05-02&cursor=x"] {
+            let (status, _) = fetch(app(&root), uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        }
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
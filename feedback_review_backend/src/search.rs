@@ -0,0 +1,494 @@
+use crate::days::{DayIndex, FileState};
+use crate::feedback::Entry;
+use crate::storage::DayDate;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+/// BM25 parameters, the usual defaults
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+/// Characters of context around the first match in a snippet
+const SNIPPET_BEFORE: usize = 60;
+const SNIPPET_AFTER: usize = 140;
+
+type DocKey = (DayDate, usize);
+
+/// Inverted index over every entry of every day.
+/// Before each search the loaded days are compared with what was indexed,
+/// and only days that changed (or appeared, or vanished) are indexed again.
+pub struct SearchIndex {
+    days: Arc<DayIndex>,
+    index: RwLock<Index>,
+}
+
+#[derive(Default)]
+struct Index {
+    /// Modification time and length of every indexed day file
+    days: HashMap<DayDate, FileState>,
+    docs: BTreeMap<DocKey, Doc>,
+    postings: HashMap<String, HashMap<DocKey, Vec<usize>>>,
+    total_tokens: usize,
+}
+
+struct Doc {
+    entry: Entry,
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    term: String,
+    start: usize,
+    end: usize,
+}
+
+/// A matching document, `spans` are the first and last token of every match
+struct Match {
+    key: DocKey,
+    score: f64,
+    spans: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Clause {
+    Term(String),
+    Phrase(Vec<String>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub from: Option<DayDate>,
+    pub to: Option<DayDate>,
+    /// Only these entries, e.g. the ones with a tag
+    pub entries: Option<HashSet<DocKey>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResults {
+    pub total: usize,
+    pub results: Vec<Hit>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Hit {
+    pub id: String,
+    pub date: DayDate,
+    pub index: usize,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub score: f64,
+    pub snippet: Vec<Segment>,
+}
+
+/// Part of a snippet, the frontend marks the highlighted parts
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Segment {
+    pub text: String,
+    pub highlight: bool,
+}
+
+impl SearchIndex {
+    pub fn new(days: Arc<DayIndex>) -> Self {
+        Self { days, index: RwLock::new(Index::default()) }
+    }
+
+    /// Indexes the days that changed since the last refresh
+    pub async fn refresh(&self) -> io::Result<()> {
+        let current = self.days.all().await?;
+
+        let (changed, removed) = {
+            let index = self.index.read().await;
+            let changed = current.iter()
+                                 .filter(|(date, day)| index.days.get(date) != Some(&day.state))
+                                 .map(|(date, day)| (*date, day.clone()))
+                                 .collect::<Vec<_>>();
+            let removed = index.days
+                               .keys()
+                               .filter(|date| !current.contains_key(date))
+                               .copied()
+                               .collect::<Vec<_>>();
+            (changed, removed)
+        };
+
+        if changed.is_empty() && removed.is_empty() {
+            return Ok(());
+        }
+
+        let mut index = self.index.write().await;
+        for date in removed {
+            index.remove_day(date);
+            index.days.remove(&date);
+        }
+        for (date, day) in changed {
+            debug!(%date, entries = day.entries.len(), "Indexing day");
+            index.remove_day(date);
+            index.add_day(date, day.entries.clone());
+            index.days.insert(date, day.state);
+        }
+
+        Ok(())
+    }
+
+    pub async fn search(&self, clauses: &[Clause], filter: &Filter, offset: usize, limit: usize) -> SearchResults {
+        let index = self.index.read().await;
+        let mut hits = index.matches(clauses, filter);
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| b.key.cmp(&a.key)));
+
+        let total = hits.len();
+        let results = hits.into_iter()
+                          .skip(offset)
+                          .take(limit)
+                          .map(|Match { key, score, spans }| {
+                              let doc = &index.docs[&key];
+                              Hit {
+                                  id: doc.entry.id.clone(),
+                                  date: doc.entry.date,
+                                  index: doc.entry.index,
+                                  timestamp: doc.entry.timestamp,
+                                  score,
+                                  snippet: snippet(&doc.entry.body, &doc.tokens, &spans),
+                              }
+                          })
+                          .collect();
+
+        SearchResults { total, results }
+    }
+}
+
+impl Index {
+    fn add_day(&mut self, date: DayDate, entries: Vec<Entry>) {
+        for entry in entries {
+            let key = (date, entry.index);
+            let tokens = tokenize(&entry.body);
+            for (position, token) in tokens.iter().enumerate() {
+                self.postings
+                    .entry(token.term.clone())
+                    .or_default()
+                    .entry(key)
+                    .or_default()
+                    .push(position);
+            }
+            self.total_tokens += tokens.len();
+            self.docs.insert(key, Doc { entry, tokens });
+        }
+    }
+
+    fn remove_day(&mut self, date: DayDate) {
+        let keys = self.docs
+                       .range((date, 0)..=(date, usize::MAX))
+                       .map(|(key, _)| *key)
+                       .collect::<Vec<_>>();
+
+        for key in keys {
+            let Some(doc) = self.docs.remove(&key) else { continue };
+            self.total_tokens -= doc.tokens.len();
+
+            let terms = doc.tokens.iter().map(|token| token.term.as_str()).collect::<HashSet<_>>();
+            for term in terms {
+                if let Some(posting) = self.postings.get_mut(term) {
+                    posting.remove(&key);
+                    if posting.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+        }
+    }
+
+    fn doc_freq(&self, term: &str) -> usize {
+        self.postings.get(term).map_or(0, HashMap::len)
+    }
+
+    /// Token positions of every occurrence of the clause in a document, as `(first, last)`
+    fn occurrences(&self, clause: &Clause, key: &DocKey) -> Vec<(usize, usize)> {
+        let positions = |term: &str| self.postings.get(term).and_then(|posting| posting.get(key));
+
+        match clause {
+            Clause::Term(term) => positions(term)
+                .map(|positions| positions.iter().map(|pos| (*pos, *pos)).collect())
+                .unwrap_or_default(),
+            Clause::Phrase(terms) => {
+                let Some(first) = positions(&terms[0]) else { return vec![] };
+                let rest = terms[1..].iter().map(|term| positions(term)).collect::<Option<Vec<_>>>();
+                let Some(rest) = rest else { return vec![] };
+
+                first.iter()
+                     .filter(|start| {
+                         rest.iter()
+                             .enumerate()
+                             .all(|(i, positions)| positions.binary_search(&(*start + i + 1)).is_ok())
+                     })
+                     .map(|start| (*start, start + terms.len() - 1))
+                     .collect()
+            }
+        }
+    }
+
+    fn matches(&self, clauses: &[Clause], filter: &Filter) -> Vec<Match> {
+        let clause_terms = |clause: &Clause| match clause {
+            Clause::Term(term) => vec![term.clone()],
+            Clause::Phrase(terms) => terms.clone(),
+        };
+
+        // Every clause has to match, so the rarest term limits the candidates
+        let Some(rarest) = clauses.iter()
+                                  .flat_map(clause_terms)
+                                  .min_by_key(|term| self.doc_freq(term)) else {
+            return vec![];
+        };
+        let Some(candidates) = self.postings.get(&rarest) else {
+            return vec![];
+        };
+
+        let doc_count = self.docs.len() as f64;
+        let avg_len = self.total_tokens as f64 / doc_count.max(1.0);
+
+        candidates.keys()
+                  .filter(|(date, _)| filter.from.is_none_or(|from| *date >= from))
+                  .filter(|(date, _)| filter.to.is_none_or(|to| *date <= to))
+                  .filter(|key| filter.entries.as_ref().is_none_or(|entries| entries.contains(*key)))
+                  .filter_map(|key| {
+                      let len = self.docs[key].tokens.len() as f64;
+                      let mut score = 0.0;
+                      let mut spans = vec![];
+
+                      for clause in clauses {
+                          let found = self.occurrences(clause, key);
+                          if found.is_empty() {
+                              return None;
+                          }
+
+                          // A phrase is as rare as its rarest term
+                          let df = clause_terms(clause).iter().map(|term| self.doc_freq(term)).min().unwrap_or(0) as f64;
+                          let idf = (1.0 + (doc_count - df + 0.5) / (df + 0.5)).ln();
+                          let tf = found.len() as f64;
+                          score += idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * len / avg_len));
+                          spans.extend(found);
+                      }
+
+                      Some(Match { key: *key, score, spans })
+                  })
+                  .collect()
+    }
+}
+
+/// Lowercased runs of alphanumeric characters, with their byte range in `text`
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut start = None;
+
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                tokens.push(Token { term: text[s..i].to_lowercase(), start: s, end: i });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+/// `login "does not work"` requires the term `login` and the phrase `does not work`
+pub fn parse_query(query: &str) -> Vec<Clause> {
+    query.split('"')
+         .enumerate()
+         .flat_map(|(i, part)| {
+             let terms = tokenize(part).into_iter().map(|token| token.term).collect::<Vec<_>>();
+             // Every second part is inside quotes
+             if i % 2 == 1 && terms.len() > 1 {
+                 vec![Clause::Phrase(terms)]
+             } else {
+                 terms.into_iter().map(Clause::Term).collect()
+             }
+         })
+         .collect()
+}
+
+/// The text around the first match, with every match inside it highlighted
+fn snippet(body: &str, tokens: &[Token], spans: &[(usize, usize)]) -> Vec<Segment> {
+    let mut ranges = spans.iter()
+                          .map(|(first, last)| (tokens[*first].start, tokens[*last].end))
+                          .collect::<Vec<_>>();
+    ranges.sort_unstable();
+
+    let Some((first_start, _)) = ranges.first().copied() else {
+        return vec![Segment { text: body.to_string(), highlight: false }];
+    };
+
+    let start = floor_char_boundary(body, first_start.saturating_sub(SNIPPET_BEFORE));
+    let end = floor_char_boundary(body, (first_start + SNIPPET_AFTER).min(body.len()));
+
+    let mut segments = vec![];
+    let plain = |segments: &mut Vec<Segment>, text: &str| {
+        if !text.is_empty() {
+            segments.push(Segment { text: text.replace('\n', " "), highlight: false });
+        }
+    };
+
+    if start > 0 {
+        plain(&mut segments, "…");
+    }
+
+    let mut cursor = start;
+    for (range_start, range_end) in ranges {
+        if range_start < cursor || range_start >= end {
+            continue;
+        }
+        let range_end = range_end.min(end);
+        plain(&mut segments, &body[cursor..range_start]);
+        segments.push(Segment { text: body[range_start..range_end].to_string(), highlight: true });
+        cursor = range_end;
+    }
+    plain(&mut segments, &body[cursor..end]);
+
+    if end < body.len() {
+        plain(&mut segments, "…");
+    }
+
+    segments
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::DayIndex;
+    use crate::FILE_SUFFIX;
+    use crate::storage::Storage;
+    use crate::test_support::{app_with, fetch, temp_root};
+    use axum::http::StatusCode;
+    use std::sync::Arc;
+
+    fn entry(date: &str, index: usize, body: &str) -> Entry {
+        let date = date.parse().unwrap();
+        Entry {
+            id: format!("{date}.{index}"),
+            date,
+            index,
+            timestamp: None,
+            body: body.to_string(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    fn index() -> Index {
+        let mut index = Index::default();
+        index.add_day("2024-05-01".parse().unwrap(), vec![
+            entry("2024-05-01", 0, "The login page does not work on my phone"),
+            entry("2024-05-01", 1, "Login works, but the page is slow"),
+        ]);
+        index.add_day("2024-05-02".parse().unwrap(), vec![
+            entry("2024-05-02", 0, "Löwen love the new login login login page"),
+        ]);
+        index
+    }
+
+    #[test]
+    fn parses_queries() {
+        assert_eq!(parse_query(r#"Login "does NOT work" x"#), vec![
+            Clause::Term(String::from("login")),
+            Clause::Phrase(vec![String::from("does"), String::from("not"), String::from("work")]),
+            Clause::Term(String::from("x")),
+        ]);
+        assert_eq!(parse_query(r#""single""#), vec![Clause::Term(String::from("single"))]);
+        assert!(parse_query(r#"" - ""#).is_empty());
+    }
+
+    #[test]
+    fn matches_terms_and_phrases() {
+        let index = index();
+
+        let hits = index.matches(&parse_query("login page"), &Filter::default());
+        assert_eq!(hits.len(), 3);
+
+        let hits = index.matches(&parse_query(r#""login page""#), &Filter::default());
+        let keys = hits.iter().map(|hit| hit.key).collect::<Vec<_>>();
+        assert_eq!(keys.len(), 2);
+        assert!(!keys.contains(&("2024-05-01".parse().unwrap(), 1)));
+
+        let hits = index.matches(&parse_query("login"), &Filter { from: Some("2024-05-02".parse().unwrap()), ..Filter::default() });
+        assert_eq!(hits.len(), 1);
+
+        assert!(index.matches(&parse_query("missing"), &Filter::default()).is_empty());
+    }
+
+    #[test]
+    fn ranks_by_frequency() {
+        let index = index();
+        let mut hits = index.matches(&parse_query("login"), &Filter::default());
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        assert_eq!(hits[0].key, ("2024-05-02".parse().unwrap(), 0));
+    }
+
+    #[test]
+    fn removes_days() {
+        let mut index = index();
+        index.remove_day("2024-05-01".parse().unwrap());
+        assert_eq!(index.docs.len(), 1);
+        assert!(!index.postings.contains_key("phone"));
+        assert_eq!(index.total_tokens, 8);
+    }
+
+    #[test]
+    fn highlights_snippets() {
+        let body = "Löwen love the new login page";
+        let tokens = tokenize(body);
+        let segments = snippet(body, &tokens, &[(4, 5)]);
+        assert_eq!(segments, vec![
+            Segment { text: String::from("Löwen love the new "), highlight: false },
+            Segment { text: String::from("login page"), highlight: true },
+        ]);
+
+        let long = format!("{} needle {}", "a ".repeat(100), "b ".repeat(100));
+        let tokens = tokenize(&long);
+        let segments = snippet(&long, &tokens, &[(100, 100)]);
+        assert_eq!(segments.first().map(|segment| segment.text.as_str()), Some("…"));
+        assert_eq!(segments.last().map(|segment| segment.text.as_str()), Some("…"));
+        assert!(segments.iter().any(|segment| segment.highlight && segment.text == "needle"));
+    }
+
+    #[tokio::test]
+    async fn searches_new_and_changed_days() {
+        let root = temp_root("search");
+        let dashes = "-".repeat(50);
+        let file = root.join(format!("2024-05-01{FILE_SUFFIX}"));
+        std::fs::write(&file, format!("{dashes}\n[2024-05-01 - 12:00:00]z\nThe login page is broken\n{dashes}\n\n")).unwrap();
+
+        let days = Arc::new(DayIndex::new(Storage::new(&root)));
+        let app = app_with(&root, "tester", days.clone());
+        let (status, body) = fetch(app.clone(), "/search?q=%22login%20page%22").await;
+        assert_eq!(status, StatusCode::OK);
+        let results: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(results["total"], 1);
+        assert_eq!(results["results"][0]["id"], "2024-05-01.0");
+        assert_eq!(results["results"][0]["snippet"][1]["text"], "login page");
+
+        // The length changes, so the day is indexed again
+        let mut content = std::fs::read_to_string(&file).unwrap();
+        content.push_str(&format!("{dashes}\n[2024-05-01 - 13:00:00]z\nStill no login\n{dashes}\n\n"));
+        std::fs::write(&file, content).unwrap();
+        days.sync().await.unwrap();
+
+        let (_, body) = fetch(app.clone(), "/search?q=login").await;
+        let results: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(results["total"], 2);
+
+        let (status, _) = fetch(app, "/search?q=%20").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+use crate::days::{DayIndex, FileState};
+use crate::feedback::Entry;
+use crate::storage::DayDate;
+use chrono::{Datelike, Timelike};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+/// Metadata key of a rating, e.g. `[meta rating=4]`
+const RATING_KEY: &str = "rating";
+
+/// What a single day contributes to the statistics
+#[derive(Debug, Default)]
+struct DayStats {
+    count: usize,
+    by_hour: [usize; 24],
+    /// Body length of every entry in characters
+    lengths: Vec<usize>,
+    ratings: BTreeMap<String, usize>,
+}
+
+/// The statistics of every day, computed again once the day file changed
+pub struct StatsCache {
+    days: Arc<DayIndex>,
+    computed: RwLock<HashMap<DayDate, (FileState, Arc<DayStats>)>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub total: usize,
+    pub per_day: Vec<Bucket>,
+    pub per_week: Vec<Bucket>,
+    pub per_month: Vec<Bucket>,
+    pub by_hour: [usize; 24],
+    pub average_length: f64,
+    pub median_length: f64,
+    /// Empty unless entries carry a rating
+    pub ratings: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Bucket {
+    pub key: String,
+    pub count: usize,
+}
+
+impl DayStats {
+    fn from_entries(entries: &[Entry]) -> Self {
+        let mut stats = Self::default();
+        for entry in entries {
+            stats.count += 1;
+            if let Some(time) = entry.timestamp {
+                stats.by_hour[time.hour() as usize] += 1;
+            }
+            stats.lengths.push(entry.body.chars().count());
+            for rating in entry.metadata.get(RATING_KEY).into_iter().flatten() {
+                *stats.ratings.entry(rating.clone()).or_default() += 1;
+            }
+        }
+        stats
+    }
+}
+
+impl StatsCache {
+    pub fn new(days: Arc<DayIndex>) -> Self {
+        Self { days, computed: RwLock::new(HashMap::new()) }
+    }
+
+    async fn day(&self, date: DayDate) -> io::Result<Option<Arc<DayStats>>> {
+        let Some(day) = self.days.day(date).await? else {
+            return Ok(None);
+        };
+
+        if let Some((state, stats)) = self.computed.read().await.get(&date)
+            && *state == day.state {
+            return Ok(Some(stats.clone()));
+        }
+
+        debug!(%date, "Computing statistics");
+        let stats = Arc::new(DayStats::from_entries(&day.entries));
+        self.computed.write().await.insert(date, (day.state, stats.clone()));
+        Ok(Some(stats))
+    }
+
+    /// `days` in any order, only days with a file are counted
+    pub async fn stats(&self, days: &[DayDate]) -> io::Result<Stats> {
+        let mut per_day = BTreeMap::new();
+        let mut per_week = BTreeMap::<String, usize>::new();
+        let mut per_month = BTreeMap::<String, usize>::new();
+        let mut by_hour = [0; 24];
+        let mut lengths = vec![];
+        let mut ratings = BTreeMap::<String, usize>::new();
+
+        for date in days {
+            let Some(day) = self.day(*date).await? else { continue };
+
+            let naive = date.as_naive();
+            let week = naive.iso_week();
+            per_day.insert(*date, day.count);
+            *per_week.entry(format!("{}-W{:02}", week.year(), week.week())).or_default() += day.count;
+            *per_month.entry(format!("{}-{:02}", naive.year(), naive.month())).or_default() += day.count;
+            for (hour, count) in day.by_hour.iter().enumerate() {
+                by_hour[hour] += count;
+            }
+            lengths.extend_from_slice(&day.lengths);
+            for (rating, count) in &day.ratings {
+                *ratings.entry(rating.clone()).or_default() += count;
+            }
+        }
+
+        lengths.sort_unstable();
+        let total = lengths.len();
+        let average_length = if total == 0 { 0.0 } else { lengths.iter().sum::<usize>() as f64 / total as f64 };
+        let median_length = match total {
+            0 => 0.0,
+            n if n % 2 == 1 => lengths[n / 2] as f64,
+            n => (lengths[n / 2 - 1] + lengths[n / 2]) as f64 / 2.0,
+        };
+
+        let buckets = |counts: BTreeMap<String, usize>| {
+            counts.into_iter()
+                  .map(|(key, count)| Bucket { key, count })
+                  .collect()
+        };
+
+        Ok(Stats {
+            total,
+            per_day: buckets(per_day.into_iter().map(|(date, count)| (date.to_string(), count)).collect()),
+            per_week: buckets(per_week),
+            per_month: buckets(per_month),
+            by_hour,
+            average_length,
+            median_length,
+            ratings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FILE_SUFFIX;
+    use crate::test_support::{app, fetch, temp_root};
+    use axum::http::StatusCode;
+
+    #[test]
+    fn counts_a_day() {
+        let dashes = "-".repeat(50);
+        let content = format!(
+            "{dashes}\n[2024-05-01 - 08:10:00]z\nabc\n{dashes}\n\n\
+             {dashes}\n[2024-05-01 - 08:50:00]z\n[meta rating=4]\nabcdefg\n{dashes}\n\n\
+             {dashes}\n[2024-05-01 - 23:00:00]z\nä\n{dashes}\n\n"
+        );
+
+        let entries = crate::feedback::parse_day("2024-05-01".parse().unwrap(), &content);
+        let stats = DayStats::from_entries(&entries);
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.by_hour[8], 2);
+        assert_eq!(stats.by_hour[23], 1);
+        assert_eq!(stats.lengths, [3, 7, 1]);
+        assert_eq!(stats.ratings.get("4"), Some(&1));
+    }
+
+    #[tokio::test]
+    async fn aggregates_stats() {
+        let root = temp_root("stats");
+        let dashes = "-".repeat(50);
+        std::fs::write(
+            root.join(format!("2024-04-30{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-04-30 - 09:00:00]z\n[meta rating=5]\nab\n{dashes}\n\n"),
+        ).unwrap();
+        std::fs::write(
+            root.join(format!("2024-05-06{FILE_SUFFIX}")),
+            format!("{dashes}\n[2024-05-06 - 09:30:00]z\nabcd\n{dashes}\n\n{dashes}\n[2024-05-06 - 17:00:00]z\nabcdefgh\n{dashes}\n\n"),
+        ).unwrap();
+
+        let (status, body) = fetch(app(&root), "/stats").await;
+        assert_eq!(status, StatusCode::OK);
+        let stats: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(stats["total"], 3);
+        assert_eq!(stats["per_day"][1], serde_json::json!({ "key": "2024-05-06", "count": 2 }));
+        assert_eq!(stats["per_week"].as_array().unwrap().len(), 2);
+        assert_eq!(stats["per_month"][0], serde_json::json!({ "key": "2024-04", "count": 1 }));
+        assert_eq!(stats["by_hour"][9], 2);
+        assert_eq!(stats["median_length"], 4.0);
+        assert_eq!(stats["ratings"], serde_json::json!({ "5": 1 }));
+
+        let (_, body) = fetch(app(&root), "/stats?from=2024-05-01").await;
+        let stats: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(stats["total"], 2);
+
+        let (status, _) = fetch(app(&root), "/stats?from=yesterday").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
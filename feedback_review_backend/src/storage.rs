@@ -0,0 +1,337 @@
+use crate::{FILE_ROOT, FILE_SUFFIX};
+use axum::extract::{FromRequestParts, Path};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{NaiveDate, Utc};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Component, Path as FsPath, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::fs;
+use tracing::{debug, warn};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DATE_PARAM: &str = "date";
+/// Locked by feedback_backend while it appends, so no entry is lost while a day file is rewritten
+const LOCK_FILE: &str = ".write.lock";
+
+/// A `YYYY-MM-DD` day, extracted from the `{date}` path segment.
+/// Anything else is rejected with 400 before it gets near the filesystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DayDate(NaiveDate);
+
+/// 400 with a machine readable `error`, e.g. `invalid_date`
+#[derive(Debug, Serialize)]
+pub struct InvalidInput {
+    error: &'static str,
+    message: String,
+    value: String,
+}
+
+/// Every file the review backend reads from the feedback volume is resolved here
+#[derive(Debug, Clone)]
+pub struct Storage {
+    root: Arc<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ResolveError {
+    Empty,
+    Separator,
+    NotNormal,
+}
+
+impl DayDate {
+    pub fn file_name(self) -> String {
+        format!("{self}{FILE_SUFFIX}")
+    }
+
+    pub fn as_naive(self) -> NaiveDate {
+        self.0
+    }
+
+    /// The day new feedback goes to, the feedback backend names day files by the UTC date
+    pub fn today() -> Self {
+        Self(Utc::now().date_naive())
+    }
+
+    /// The date of a day file, `None` for every other file in the storage root
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        file_name.strip_suffix(FILE_SUFFIX)?
+                 .parse()
+                 .ok()
+    }
+
+    /// For optional dates in query strings
+    pub fn parse_param(param: Option<&str>) -> Result<Option<Self>, InvalidInput> {
+        param.map(|value| value.parse().map_err(|message| InvalidInput::date(message, value)))
+             .transpose()
+    }
+}
+
+impl InvalidInput {
+    pub fn new(error: &'static str, message: impl Into<String>, value: impl Into<String>) -> Self {
+        Self { error, message: message.into(), value: value.into() }
+    }
+
+    pub fn date(message: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::new("invalid_date", message, value)
+    }
+}
+
+impl FromStr for DayDate {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        // chrono alone accepts e.g. `+2024-1-1`, only the exact shape is allowed
+        let shape_ok = value.len() == 10
+            && value.bytes().enumerate().all(|(i, b)| match i {
+                4 | 7 => b == b'-',
+                _ => b.is_ascii_digit(),
+            });
+        if !shape_ok {
+            return Err(String::from("Expected a date formatted as YYYY-MM-DD"));
+        }
+
+        NaiveDate::parse_from_str(value, DATE_FORMAT)
+            .map(Self)
+            .map_err(|e| format!("Not a valid date: {e}"))
+    }
+}
+
+impl Display for DayDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.format(DATE_FORMAT))
+    }
+}
+
+impl Serialize for DayDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DayDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl<S> FromRequestParts<S> for DayDate
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        let value = params.get(DATE_PARAM).cloned().unwrap_or_default();
+        value.parse().map_err(|message| {
+            warn!(value, "Rejected invalid date");
+            InvalidInput::date(message, value).into_response()
+        })
+    }
+}
+
+impl IntoResponse for InvalidInput {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, Json(self)).into_response()
+    }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "file name is empty"),
+            Self::Separator => write!(f, "file name contains a path separator"),
+            Self::NotNormal => write!(f, "file name is not a plain file name"),
+        }
+    }
+}
+
+impl Storage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: Arc::new(root.into()) }
+    }
+
+    pub fn root(&self) -> &FsPath {
+        &self.root
+    }
+
+    /// Only accepts a single plain file name, so the result is always directly inside the root
+    pub fn resolve(&self, file_name: &str) -> Result<PathBuf, ResolveError> {
+        if file_name.is_empty() {
+            return Err(ResolveError::Empty);
+        }
+        if file_name.contains(['/', '\\', '\0']) {
+            return Err(ResolveError::Separator);
+        }
+
+        let mut components = FsPath::new(file_name).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => Ok(self.root.join(file_name)),
+            _ => Err(ResolveError::NotNormal),
+        }
+    }
+
+    pub fn day_file(&self, date: DayDate) -> PathBuf {
+        // A valid date can't contain anything that would be rejected
+        self.resolve(&date.file_name())
+            .expect("day file names are plain file names")
+    }
+
+    /// The content of a day file, `None` if there is no feedback for that day
+    pub async fn read_day(&self, date: DayDate) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.day_file(date)).await {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Metadata of a day file, `None` if there is no feedback for that day
+    pub async fn day_metadata(&self, date: DayDate) -> io::Result<Option<std::fs::Metadata>> {
+        match fs::metadata(self.day_file(date)).await {
+            Ok(meta) => Ok(Some(meta)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replaces a day file with what `f` makes of its content, `false` if there is no file
+    /// or `f` returns `None`. The new content is written to a temporary file that is renamed
+    /// over the day file, so readers see either the old or the new file, never a mix.
+    pub async fn rewrite_day(
+        &self,
+        date: DayDate,
+        f: impl FnOnce(&str) -> Option<String> + Send + 'static,
+    ) -> io::Result<bool> {
+        let path = self.day_file(date);
+        let tmp = path.with_file_name(format!("{}.tmp", date.file_name()));
+        let lock_path = self.root.join(LOCK_FILE);
+
+        tokio::task::spawn_blocking(move || {
+            let lock = OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path)?;
+            lock.lock()?;
+
+            let (content, permissions) = match std::fs::read_to_string(&path) {
+                Ok(content) => (content, std::fs::metadata(&path)?.permissions()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+                Err(e) => return Err(e),
+            };
+            let Some(content) = f(&content) else {
+                return Ok(false);
+            };
+
+            let mut file = File::create(&tmp)?;
+            file.write_all(content.as_bytes())?;
+            file.set_permissions(permissions)?;
+            file.sync_all()?;
+            std::fs::rename(&tmp, &path)?;
+            debug!("Rewrote {}", path.display());
+            Ok(true)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+
+    /// Every day with a feedback file, newest first. Other files in the root are skipped.
+    pub async fn days(&self) -> io::Result<Vec<DayDate>> {
+        let mut days = vec![];
+        let mut dir = fs::read_dir(self.root.as_path()).await?;
+
+        while let Some(entry) = dir.next_entry().await? {
+            let Some(date) = entry.file_name()
+                                  .to_str()
+                                  .and_then(DayDate::from_file_name) else {
+                debug!("Skipping {:?}, not a day file", entry.file_name());
+                continue;
+            };
+            if entry.file_type().await?.is_file() {
+                days.push(date);
+            }
+        }
+
+        days.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(days)
+    }
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self::new(FILE_ROOT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_dates() {
+        let date = "2024-02-29".parse::<DayDate>().unwrap();
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert_eq!(date.file_name(), format!("2024-02-29{FILE_SUFFIX}"));
+    }
+
+    #[test]
+    fn rejects_malformed_dates() {
+        for value in [
+            "", "2024-1-01", "2024-01-1", "+2024-01-01", " 2024-01-01", "2024-01-01 ",
+            "2024/01/01", "20240101", "2024-02-30", "2023-02-29", "2024-13-01", "2024-00-10",
+            "２０２４-01-01", "2024-01-01\0",
+        ] {
+            assert!(value.parse::<DayDate>().is_err(), "{value:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn rejects_traversal_in_dates() {
+        for value in [
+            "..", "../", "../../etc/passwd", "..%2F..%2Fetc%2Fpasswd", "2024-01-01/../../x",
+            "/etc/passwd", "..\\..\\windows", "2024-01-01%00", "....-..-..",
+        ] {
+            assert!(value.parse::<DayDate>().is_err(), "{value:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn parses_day_file_names() {
+        let date = DayDate::from_file_name(&format!("2024-05-01{FILE_SUFFIX}"));
+        assert_eq!(date.map(|date| date.to_string()).as_deref(), Some("2024-05-01"));
+        assert_eq!(DayDate::from_file_name("2024-05-01"), None);
+        assert_eq!(DayDate::from_file_name(&format!("..{FILE_SUFFIX}")), None);
+        assert_eq!(DayDate::from_file_name(".readyz"), None);
+    }
+
+    #[test]
+    fn resolves_inside_the_root() {
+        let storage = Storage::new("/feedback");
+        let date = "2024-05-01".parse().unwrap();
+        assert_eq!(storage.day_file(date), PathBuf::from(format!("/feedback/2024-05-01{FILE_SUFFIX}")));
+        assert_eq!(storage.resolve("a.txt"), Ok(PathBuf::from("/feedback/a.txt")));
+    }
+
+    #[test]
+    fn resolver_rejects_escapes() {
+        let storage = Storage::new("/feedback");
+        assert_eq!(storage.resolve(""), Err(ResolveError::Empty));
+        assert_eq!(storage.resolve(".."), Err(ResolveError::NotNormal));
+        assert_eq!(storage.resolve("."), Err(ResolveError::NotNormal));
+        for name in ["../etc/passwd", "/etc/passwd", "a/../../b", "..\\x", "a\0b"] {
+            assert_eq!(storage.resolve(name), Err(ResolveError::Separator), "{name:?}");
+        }
+    }
+}
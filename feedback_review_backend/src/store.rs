@@ -0,0 +1,118 @@
+use crate::DATA_ROOT;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tokio::fs;
+use tokio::sync::RwLock;
+use tracing::{debug, error};
+
+/// A JSON file in [`DATA_ROOT`], cached in memory.
+/// The CLI changes these files while the server is running,
+/// so the file is read again whenever its modification time changed.
+pub struct JsonStore<T> {
+    path: PathBuf,
+    cache: RwLock<Cached<T>>,
+}
+
+struct Cached<T> {
+    modified: Option<SystemTime>,
+    value: T,
+}
+
+impl<T> JsonStore<T>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    pub fn new(file_name: &str) -> Self {
+        Self::at(PathBuf::from(DATA_ROOT).join(file_name))
+    }
+
+    /// A store outside of [`DATA_ROOT`], or in one of its subdirectories
+    pub fn at(path: PathBuf) -> Self {
+        Self {
+            path,
+            cache: RwLock::new(Cached { modified: None, value: T::default() }),
+        }
+    }
+
+    async fn modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path)
+            .await
+            .and_then(|meta| meta.modified())
+            .ok()
+    }
+
+    async fn load(&self) -> Result<T> {
+        match fs::read_to_string(&self.path).await {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", self.path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(T::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        }
+    }
+
+    /// Writes to a temporary file first, so a crash never leaves a half written store behind
+    async fn save(&self, value: &T) -> Result<()> {
+        let tmp = self.path.with_extension("json.tmp");
+        let content = serde_json::to_string_pretty(value)
+            .with_context(|| format!("Failed to serialize {}", self.path.display()))?;
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .await
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(&tmp, content)
+            .await
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &self.path)
+            .await
+            .with_context(|| format!("Failed to replace {}", self.path.display()))
+    }
+
+    async fn refresh(&self, cache: &mut Cached<T>) -> Result<()> {
+        let modified = self.modified().await;
+        if cache.modified != modified || modified.is_none() {
+            cache.value = self.load().await?;
+            cache.modified = modified;
+            debug!("Reloaded {}", self.path.display());
+        }
+        Ok(())
+    }
+
+    /// Runs `f` on the current content, if reloading fails the last known content is used
+    pub async fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let modified = self.modified().await;
+        {
+            let cache = self.cache.read().await;
+            if cache.modified == modified && modified.is_some() {
+                return f(&cache.value);
+            }
+        }
+
+        let mut cache = self.cache.write().await;
+        if let Err(e) = self.refresh(&mut cache).await {
+            error!("Failed to reload {}: {e:#}", self.path.display());
+        }
+        f(&cache.value)
+    }
+
+    /// Runs `f` on the freshly loaded content and saves the result if `f` succeeds
+    pub async fn update<R>(&self, f: impl FnOnce(&mut T) -> Result<R>) -> Result<R> {
+        let mut cache = self.cache.write().await;
+        self.refresh(&mut cache).await?;
+
+        let result = f(&mut cache.value)?;
+        if let Err(e) = self.save(&cache.value).await {
+            // The cached content is ahead of the file now, read it again next time
+            cache.modified = None;
+            return Err(e);
+        }
+        cache.modified = self.modified().await;
+
+        Ok(result)
+    }
+}
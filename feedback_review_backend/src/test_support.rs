@@ -0,0 +1,123 @@
+//! Fixtures for the route tests, every test gets its own storage root and the real router
+
+use crate::access::AccessLog;
+use crate::annotations::Annotations;
+use crate::audit::AuditLog;
+use crate::auth::Auth;
+use crate::days::DayIndex;
+use crate::search::SearchIndex;
+use crate::stats::StatsCache;
+use crate::storage::Storage;
+use crate::{router, AppState};
+use axum::body::{self, Body};
+use axum::http::{header, HeaderValue, Request, StatusCode};
+use axum::middleware;
+use axum::Router;
+use feedback_telemetry::cors::CorsPolicy;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+pub fn temp_root(name: &str) -> PathBuf {
+    let root = env::temp_dir().join(format!("feedback_review_{name}_{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
+
+pub fn app(root: &Path) -> Router {
+    app_as(root, "tester")
+}
+
+pub fn app_as(root: &Path, name: &str) -> Router {
+    app_with(root, name, Arc::new(DayIndex::new(Storage::new(root))))
+}
+
+/// The token a test user called `name` authenticates with
+pub fn token(name: &str) -> String {
+    format!("frb_{name}")
+}
+
+/// Stores a token for `name` like `feedback_review_backend token add` would
+pub fn add_token(root: &Path, name: &str, roles: &[&str]) {
+    let dir = root.join("review_data");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tokens.json");
+    let mut tokens = std::fs::read_to_string(&path)
+        .map(|content| serde_json::from_str::<Vec<serde_json::Value>>(&content).unwrap())
+        .unwrap_or_default();
+    tokens.retain(|stored| stored["name"] != name);
+    let hash = Sha256::digest(token(name).as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+    tokens.push(serde_json::json!({ "name": name, "hash": hash, "created": "2024-05-01T00:00:00Z", "roles": roles }));
+    std::fs::write(path, serde_json::to_string(&tokens).unwrap()).unwrap();
+}
+
+pub fn state(root: &Path, days: Arc<DayIndex>) -> AppState {
+    AppState {
+        auth: Arc::new(Auth::in_dir(&root.join("review_data"))),
+        search: Arc::new(SearchIndex::new(days.clone())),
+        stats: Arc::new(StatsCache::new(days.clone())),
+        days,
+        annotations: Arc::new(Annotations::new(root.join("annotations"))),
+        audit: Arc::new(AuditLog::new(root.join("review_data").join("audit.log"))),
+        access: Arc::new(AccessLog::new(root.join("review_data").join("access.log"))),
+        storage: Storage::new(root),
+    }
+}
+
+/// The real router without credentials
+pub fn anonymous_app(root: &Path) -> Router {
+    let days = Arc::new(DayIndex::new(Storage::new(root)));
+    router(CorsPolicy::parse("https://example.com").unwrap(), state(root, days))
+}
+
+/// The real router, every request carries the token of the admin `name`.
+/// Without a watcher, tests that change files call [`DayIndex::sync`] themselves.
+pub fn app_with(root: &Path, name: &str, days: Arc<DayIndex>) -> Router {
+    add_token(root, name, &["admin"]);
+    let authorization = HeaderValue::try_from(format!("Bearer {}", token(name))).unwrap();
+    router(CorsPolicy::parse("https://example.com").unwrap(), state(root, days))
+        .layer(middleware::map_request(move |mut req: Request<Body>| {
+            let authorization = authorization.clone();
+            async move {
+                req.headers_mut().insert(header::AUTHORIZATION, authorization);
+                req
+            }
+        }))
+}
+
+pub async fn fetch(app: Router, uri: &str) -> (StatusCode, String) {
+    let res = app.oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+    let status = res.status();
+    let body = body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).into_owned())
+}
+
+pub async fn send(app: Router, method: &str, uri: &str, json: &str) -> (StatusCode, String) {
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json.to_string()))
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    let status = res.status();
+    let body = body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).into_owned())
+}
+
+pub async fn status_of(app: Router, method: &str, uri: &str, credentials: Option<(header::HeaderName, String)>) -> StatusCode {
+    let mut req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some((name, value)) = credentials {
+        req = req.header(name, value);
+    }
+    // Only the status, `/events` never ends
+    app.oneshot(req.body(Body::from("{}")).unwrap()).await.unwrap().status()
+}
@@ -0,0 +1,168 @@
+use crate::components::footer::Footer;
+use crate::functions::{get_access_log, AccessEvent, AccessFilter, AccessPage};
+use crate::Route;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+const INPUT_CLASSES: &[&str] = &["p-2", "border", "border-gray-300", "rounded", "dark:bg-gray-700", "dark:border-gray-600"];
+const CELL_CLASSES: &[&str] = &["px-2", "py-1", "align-top"];
+
+fn bind(state: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let state = state.clone();
+    Callback::from(move |e: InputEvent| {
+        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+            state.set(input.value());
+        }
+    })
+}
+
+/// Who requested what from the review backend, newest first. Only for admins.
+#[function_component(AccessLog)]
+pub fn access_log() -> Html {
+    gloo::utils::document().set_title("Feedback Review - Access log");
+
+    let filter = use_state(AccessFilter::default);
+    let page = use_state(|| None::<Result<AccessPage, String>>);
+    let loading_more = use_state(|| false);
+    let user = use_state(String::new);
+    let route = use_state(String::new);
+    let day = use_state(String::new);
+    let from = use_state(String::new);
+    let to = use_state(String::new);
+
+    {
+        let page = page.clone();
+        use_effect_with((*filter).clone(), move |filter| {
+            page.set(None);
+            let filter = filter.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                page.set(Some(get_access_log(&filter, 0).await));
+            });
+            || ()
+        });
+    }
+
+    let on_submit = {
+        let filter = filter.clone();
+        let (user, route, day, from, to) = (user.clone(), route.clone(), day.clone(), from.clone(), to.clone());
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            filter.set(AccessFilter {
+                user: user.trim().to_string(),
+                route: route.trim().to_string(),
+                day: (*day).clone(),
+                from: (*from).clone(),
+                to: (*to).clone(),
+            });
+        })
+    };
+
+    let on_load_more = {
+        let page = page.clone();
+        let loading_more = loading_more.clone();
+        let filter = filter.clone();
+        Callback::from(move |_| {
+            let Some(Ok(current)) = (*page).clone() else { return };
+
+            let page = page.clone();
+            let loading_more = loading_more.clone();
+            let filter = (*filter).clone();
+            loading_more.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_access_log(&filter, current.events.len()).await {
+                    Ok(next) => {
+                        let mut all = current.events;
+                        all.extend(next.events);
+                        page.set(Some(Ok(AccessPage { total: next.total, events: all })));
+                    }
+                    Err(e) => page.set(Some(Err(e))),
+                }
+                loading_more.set(false);
+            });
+        })
+    };
+
+    html! {
+        <>
+            <h1 class={classes!("text-3xl", "font-bold", "mb-6")}>{ "Access log" }</h1>
+            <form onsubmit={on_submit} class={classes!("w-full", "max-w-4xl", "flex", "flex-wrap", "gap-2", "mb-6")}>
+                <input placeholder="User" value={(*user).clone()} oninput={bind(&user)} class={classes!("w-32", INPUT_CLASSES)}/>
+                <input
+                    placeholder="Route, e.g. /feedback/{date}/entries"
+                    value={(*route).clone()}
+                    oninput={bind(&route)}
+                    class={classes!("flex-1", "min-w-0", INPUT_CLASSES)}
+                />
+                <input type="date" title="Feedback of this day" value={(*day).clone()} oninput={bind(&day)} class={classes!(INPUT_CLASSES)}/>
+                <input type="date" title="Requested from" value={(*from).clone()} oninput={bind(&from)} class={classes!(INPUT_CLASSES)}/>
+                <input type="date" title="Requested until" value={(*to).clone()} oninput={bind(&to)} class={classes!(INPUT_CLASSES)}/>
+                <button
+                    type="submit"
+                    class={classes!("bg-indigo-700", "hover:bg-indigo-800", "text-white", "font-bold", "py-2", "px-4", "rounded")}
+                >
+                    { "Filter" }
+                </button>
+            </form>
+            {
+                match &*page {
+                    None => html! { <p>{ "Loading..." }</p> },
+                    Some(Err(e)) => html! { <p class="error">{ format!("Error: {e}") }</p> },
+                    Some(Ok(page)) => html! {
+                        <>
+                            <p class={classes!("mb-4", "text-gray-500", "dark:text-gray-400")}>
+                                { format!("{} requests", page.total) }
+                            </p>
+                            <table class={classes!("w-full", "max-w-4xl", "text-sm", "text-left")}>
+                                <thead>
+                                    <tr class={classes!("border-b", "border-gray-300", "dark:border-gray-600")}>
+                                        { for ["Time (UTC)", "User", "Request", "Parameters", "Status", "Bytes"].iter().map(|label| html! {
+                                            <th class={classes!(CELL_CLASSES)}>{ *label }</th>
+                                        }) }
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    { for page.events.iter().map(event_row) }
+                                </tbody>
+                            </table>
+                            if page.events.len() < page.total {
+                                <button
+                                    onclick={on_load_more}
+                                    disabled={*loading_more}
+                                    class={classes!("mt-6", "bg-indigo-700", "hover:bg-indigo-800", "text-white", "font-bold", "py-2", "px-4", "rounded")}
+                                >
+                                    { if *loading_more { "Loading..." } else { "Load more" } }
+                                </button>
+                            }
+                        </>
+                    },
+                }
+            }
+            <Link<Route> to={Route::Home}>
+                <a class={classes!("mt-6", "inline-block", "text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                    { "Back to Home" }
+                </a>
+            </Link<Route>>
+            <Footer/>
+        </>
+    }
+}
+
+fn event_row(event: &AccessEvent) -> Html {
+    let params = event.params
+                      .iter()
+                      .map(|(key, value)| format!("{key}={value}"))
+                      .collect::<Vec<_>>()
+                      .join(" ");
+
+    html! {
+        <tr class={classes!("border-b", "border-gray-200", "dark:border-gray-700")}>
+            <td class={classes!(CELL_CLASSES, "whitespace-nowrap")}>{ event.at.get(..19).unwrap_or(&event.at).replace('T', " ") }</td>
+            <td class={classes!(CELL_CLASSES)}>{ &event.user }</td>
+            <td class={classes!(CELL_CLASSES)}>{ format!("{} {}", event.method, event.route) }</td>
+            <td class={classes!(CELL_CLASSES, "break-all")}>{ params }</td>
+            <td class={classes!(CELL_CLASSES, (event.status >= 400).then_some("text-red-600"))}>{ event.status }</td>
+            <td class={classes!(CELL_CLASSES)}>{ event.size }</td>
+        </tr>
+    }
+}
@@ -0,0 +1,92 @@
+use crate::functions::Assignment;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct AssignmentControlProps {
+    pub assignment: Option<Assignment>,
+    /// Resolved or archived, so it can't be overdue
+    pub done: bool,
+    /// Without it the assignment is only shown
+    pub editable: bool,
+    pub reviewers: Vec<String>,
+    /// The new assignee (`None` unassigns) and due date
+    pub on_change: Callback<(Option<String>, Option<String>)>,
+}
+
+#[function_component(AssignmentControl)]
+pub fn assignment_control(props: &AssignmentControlProps) -> Html {
+    let assignee = props.assignment.as_ref().map(|assignment| assignment.assignee.clone());
+    let due = props.assignment.as_ref().and_then(|assignment| assignment.due.clone());
+    let overdue = !props.done && props.assignment.as_ref().is_some_and(Assignment::past_due);
+
+    let on_assignee = {
+        let (due, on_change) = (due.clone(), props.on_change.clone());
+        Callback::from(move |e: Event| {
+            let assignee = e.target_unchecked_into::<HtmlSelectElement>().value();
+            let assignee = Some(assignee).filter(|assignee| !assignee.is_empty());
+            // Unassigning drops the due date as well
+            on_change.emit((assignee.clone(), due.clone().filter(|_| assignee.is_some())));
+        })
+    };
+
+    let on_due = {
+        let (assignee, on_change) = (assignee.clone(), props.on_change.clone());
+        Callback::from(move |e: Event| {
+            let due = e.target_unchecked_into::<HtmlInputElement>().value();
+            on_change.emit((assignee.clone(), Some(due).filter(|due| !due.is_empty())));
+        })
+    };
+
+    let highlight = if overdue {
+        classes!("text-red-600", "dark:text-red-400", "font-bold")
+    } else {
+        classes!()
+    };
+
+    if !props.editable {
+        return match &props.assignment {
+            Some(assignment) => html! {
+                <span class={highlight}>
+                    { format!("Assigned to {}", assignment.assignee) }
+                    if let Some(due) = &assignment.due {
+                        { format!(", due {due}") }
+                    }
+                    if overdue {
+                        { " (overdue)" }
+                    }
+                </span>
+            },
+            None => html! {},
+        };
+    }
+
+    // Keeps the assignee selectable if their account was removed
+    let mut reviewers = props.reviewers.clone();
+    if let Some(assignee) = &assignee
+        && !reviewers.contains(assignee) {
+        reviewers.push(assignee.clone());
+    }
+
+    html! {
+        <div class={classes!("flex", "items-center", "gap-1", highlight)}>
+            <select onchange={on_assignee} title="Assignee" class={classes!("rounded", "bg-gray-100", "dark:bg-gray-600", "px-2", "py-1")}>
+                <option value="" selected={assignee.is_none()}>{ "Unassigned" }</option>
+                {
+                    for reviewers.iter().map(|reviewer| html! {
+                        <option value={reviewer.clone()} selected={assignee.as_ref() == Some(reviewer)}>{ reviewer }</option>
+                    })
+                }
+            </select>
+            if assignee.is_some() {
+                <input
+                    type="date"
+                    title={if overdue { "Due date (overdue)" } else { "Due date" }}
+                    value={due.unwrap_or_default()}
+                    onchange={on_due}
+                    class={classes!("rounded", "bg-gray-100", "dark:bg-gray-600", "px-2", "py-1")}
+                />
+            }
+        </div>
+    }
+}
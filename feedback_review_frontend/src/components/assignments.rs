@@ -0,0 +1,92 @@
+use crate::components::footer::Footer;
+use crate::functions::{get_my_assignments, AssignedEntry, STATUSES};
+use crate::Route;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// The entries assigned to the logged in reviewer, due first
+#[function_component(Assignments)]
+pub fn assignments() -> Html {
+    gloo::utils::document().set_title("Feedback Review - My assignments");
+
+    let assigned = use_state(|| None::<Result<Vec<AssignedEntry>, String>>);
+    let show_done = use_state(|| false);
+
+    {
+        let assigned = assigned.clone();
+        use_effect_with(*show_done, move |show_done| {
+            let show_done = *show_done;
+            wasm_bindgen_futures::spawn_local(async move {
+                assigned.set(Some(get_my_assignments(show_done).await));
+            });
+            || ()
+        });
+    }
+
+    let on_toggle_done = {
+        let show_done = show_done.clone();
+        Callback::from(move |_| show_done.set(!*show_done))
+    };
+
+    html! {
+        <>
+            <h1 class={classes!("text-3xl", "font-bold", "mb-6")}>{ "My assignments" }</h1>
+            <label class={classes!("mb-4", "text-sm", "flex", "items-center", "gap-2")}>
+                <input type="checkbox" checked={*show_done} onchange={on_toggle_done}/>
+                { "Show resolved and archived" }
+            </label>
+            {
+                match &*assigned {
+                    None => html! { <p>{ "Loading..." }</p> },
+                    Some(Err(e)) => html! { <p class="error">{ format!("Error: {e}") }</p> },
+                    Some(Ok(assigned)) if assigned.is_empty() => html! { <p>{ "Nothing assigned to you" }</p> },
+                    Some(Ok(assigned)) => html! {
+                        <ul class={classes!("space-y-4", "w-full", "max-w-4xl")}>
+                            {
+                                for assigned.iter().map(|assigned| {
+                                    let due = assigned.annotation.assignment.as_ref().and_then(|assignment| assignment.due.clone());
+                                    let status = STATUSES.iter()
+                                                         .find(|(status, _)| *status == assigned.annotation.status)
+                                                         .map_or(assigned.annotation.status.as_str(), |(_, label)| label);
+                                    html! {
+                                        <li
+                                            key={assigned.entry.id.clone()}
+                                            class={classes!(
+                                                "p-4", "border", "rounded-lg", "dark:bg-gray-700",
+                                                if assigned.overdue { classes!("border-red-500") } else { classes!("border-gray-200", "dark:border-gray-600") },
+                                            )}
+                                        >
+                                            <div class={classes!("flex", "justify-between", "text-sm", "text-gray-500", "dark:text-gray-400", "mb-2")}>
+                                                <Link<Route> to={Route::Date { date: assigned.entry.date.clone() }}>
+                                                    <a class={classes!("text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                                                        { format!("{} {}", assigned.entry.date, assigned.entry.time().unwrap_or_default()) }
+                                                    </a>
+                                                </Link<Route>>
+                                                <span class={classes!(assigned.overdue.then_some(classes!("text-red-600", "dark:text-red-400", "font-bold")))}>
+                                                    { status }
+                                                    if let Some(due) = due {
+                                                        { format!(", due {due}") }
+                                                    }
+                                                    if assigned.overdue {
+                                                        { " (overdue)" }
+                                                    }
+                                                </span>
+                                            </div>
+                                            { for assigned.entry.body.lines().map(|line| html! { <p>{ line }</p> }) }
+                                        </li>
+                                    }
+                                })
+                            }
+                        </ul>
+                    },
+                }
+            }
+            <Link<Route> to={Route::Home}>
+                <a class={classes!("mt-6", "inline-block", "text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                    { "Back to Home" }
+                </a>
+            </Link<Route>>
+            <Footer/>
+        </>
+    }
+}
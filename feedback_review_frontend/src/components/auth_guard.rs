@@ -0,0 +1,67 @@
+use crate::functions::{get_me, logout, Me, UNAUTHORIZED};
+use crate::Route;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct AuthGuardProps {
+    pub children: Html,
+    #[prop_or("read")]
+    pub permission: &'static str,
+}
+
+/// Only renders its children for logged in reviewers, everyone else is sent to the login page.
+/// The children get the current user as `Me` context, e.g. to hide controls.
+#[function_component(AuthGuard)]
+pub fn auth_guard(props: &AuthGuardProps) -> Html {
+    let navigator = use_navigator().unwrap();
+    let me = use_state(|| None::<Result<Me, String>>);
+
+    {
+        let me = me.clone();
+        let navigator = navigator.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = get_me().await;
+                if matches!(&result, Err(e) if e == UNAUTHORIZED) {
+                    navigator.replace(&Route::Login);
+                    return;
+                }
+                me.set(Some(result));
+            });
+            || ()
+        });
+    }
+
+    let on_logout = Callback::from(move |_| {
+        let navigator = navigator.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = logout().await {
+                gloo::console::error!(e);
+            }
+            navigator.push(&Route::Login);
+        });
+    });
+
+    match &*me {
+        None => html! { <p>{ "Loading..." }</p> },
+        Some(Err(err)) => html! { <p class="error">{ format!("Error: {err}") }</p> },
+        Some(Ok(me)) if !me.can(props.permission) => html! {
+            <p class="error">{ format!("Error: {} may not {}", me.name, props.permission) }</p>
+        },
+        Some(Ok(me)) => html! {
+            <ContextProvider<Me> context={me.clone()}>
+                <div class={classes!("w-full", "max-w-3xl", "flex", "justify-end", "items-center", "gap-4", "mb-4", "text-sm")}>
+                    <span class={classes!("text-gray-500", "dark:text-gray-400")}>{ format!("Logged in as {} ({})", me.name, me.roles.join(", ")) }</span>
+                    <button
+                        onclick={on_logout}
+                        class={classes!("text-blue-500", "dark:text-blue-400", "hover:underline")}
+                    >
+                        { "Log out" }
+                    </button>
+                </div>
+                { props.children.clone() }
+            </ContextProvider<Me>>
+        },
+    }
+}
@@ -0,0 +1,216 @@
+use crate::functions::{add_comment, edit_comment, Comment, Me};
+use pulldown_cmark::{html, CowStr, Event, Parser, Tag};
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+const SAFE_SCHEMES: [&str; 3] = ["https://", "http://", "mailto:"];
+const TEXTAREA_CLASSES: &[&str] = &["w-full", "p-2", "border", "border-gray-300", "rounded", "dark:bg-gray-800", "dark:border-gray-600"];
+const BUTTON_CLASSES: &[&str] = &["text-sm", "bg-indigo-700", "hover:bg-indigo-800", "text-white", "py-1", "px-3", "rounded"];
+
+#[derive(Properties, PartialEq)]
+pub struct CommentThreadProps {
+    pub date: String,
+    pub index: usize,
+    pub comments: Vec<Comment>,
+    /// The whole thread after a comment was added or edited
+    pub on_change: Callback<Vec<Comment>>,
+}
+
+/// Collapsed to a "N comments" toggle until opened
+#[function_component(CommentThread)]
+pub fn comment_thread(props: &CommentThreadProps) -> Html {
+    let me = use_context::<Me>();
+    let can_comment = me.as_ref().is_some_and(|me| me.can("moderate"));
+    let open = use_state(|| false);
+    let draft = use_state(String::new);
+    // The comment being edited and its new body
+    let editing = use_state(|| None::<(usize, String)>);
+    let error = use_state(|| None::<String>);
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(!*open))
+    };
+
+    let on_post = {
+        let (draft, error) = (draft.clone(), error.clone());
+        let (date, index) = (props.date.clone(), props.index);
+        let (comments, on_change) = (props.comments.clone(), props.on_change.clone());
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            if draft.trim().is_empty() {
+                return;
+            }
+
+            let (draft, error) = (draft.clone(), error.clone());
+            let (date, comments, on_change) = (date.clone(), comments.clone(), on_change.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                match add_comment(&date, index, &draft).await {
+                    Ok(comment) => {
+                        let mut comments = comments;
+                        comments.push(comment);
+                        on_change.emit(comments);
+                        draft.set(String::new());
+                        error.set(None);
+                    }
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+
+    let on_save = {
+        let (editing, error) = (editing.clone(), error.clone());
+        let (date, index) = (props.date.clone(), props.index);
+        let (comments, on_change) = (props.comments.clone(), props.on_change.clone());
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let Some((id, body)) = (*editing).clone() else { return };
+
+            let (editing, error) = (editing.clone(), error.clone());
+            let (date, comments, on_change) = (date.clone(), comments.clone(), on_change.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                match edit_comment(&date, index, id, &body).await {
+                    Ok(edited) => {
+                        on_change.emit(comments.into_iter()
+                                               .map(|comment| if comment.id == id { edited.clone() } else { comment })
+                                               .collect());
+                        editing.set(None);
+                        error.set(None);
+                    }
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+
+    let on_draft = {
+        let draft = draft.clone();
+        Callback::from(move |e: InputEvent| draft.set(e.target_unchecked_into::<HtmlTextAreaElement>().value()))
+    };
+
+    let on_edit_input = {
+        let editing = editing.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some((id, _)) = *editing {
+                editing.set(Some((id, e.target_unchecked_into::<HtmlTextAreaElement>().value())));
+            }
+        })
+    };
+
+    let on_edit = |comment: &Comment| {
+        let editing = editing.clone();
+        let (id, body) = (comment.id, comment.body.clone());
+        Callback::from(move |_| editing.set(Some((id, body.clone()))))
+    };
+
+    let on_cancel = {
+        let editing = editing.clone();
+        Callback::from(move |_| editing.set(None))
+    };
+
+    let label = match props.comments.len() {
+        0 if can_comment => String::from("Comment"),
+        0 => return html! {},
+        1 => String::from("1 comment"),
+        count => format!("{count} comments"),
+    };
+
+    html! {
+        <div class={classes!("mt-2", "text-base")}>
+            <button onclick={on_toggle} class={classes!("text-sm", "text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                { if *open { String::from("Hide comments") } else { label } }
+            </button>
+            if *open {
+                <ul class={classes!("mt-2", "space-y-3", "border-l-2", "border-gray-300", "dark:border-gray-500", "pl-3")}>
+                    {
+                        for props.comments.iter().map(|comment| {
+                            let own = me.as_ref().is_some_and(|me| me.name == comment.author);
+                            html! {
+                                <li key={comment.id}>
+                                    <div class={classes!("text-sm", "text-gray-500", "dark:text-gray-400")}>
+                                        <span class="font-bold">{ &comment.author }</span>
+                                        { format!(" {}", short_time(&comment.created)) }
+                                        if !comment.history.is_empty() {
+                                            <span title={history_title(comment)}>{ " (edited)" }</span>
+                                        }
+                                        if own && can_comment && editing.is_none() {
+                                            <button onclick={on_edit(comment)} class={classes!("ml-2", "hover:underline")}>{ "Edit" }</button>
+                                        }
+                                    </div>
+                                    {
+                                        match &*editing {
+                                            Some((id, body)) if *id == comment.id => html! {
+                                                <form onsubmit={on_save.clone()} class={classes!("space-y-1")}>
+                                                    <textarea value={body.clone()} oninput={on_edit_input.clone()} rows="3" class={classes!(TEXTAREA_CLASSES)}/>
+                                                    <button type="submit" class={classes!(BUTTON_CLASSES)}>{ "Save" }</button>
+                                                    <button type="button" onclick={on_cancel.clone()} class={classes!("ml-2", "text-sm", "hover:underline")}>{ "Cancel" }</button>
+                                                </form>
+                                            },
+                                            _ => html! { <div class="markdown">{ markdown(&comment.body) }</div> },
+                                        }
+                                    }
+                                </li>
+                            }
+                        })
+                    }
+                </ul>
+                if can_comment {
+                    <form onsubmit={on_post} class={classes!("mt-3", "space-y-1")}>
+                        <textarea
+                            value={(*draft).clone()}
+                            oninput={on_draft}
+                            rows="2"
+                            placeholder="Add a comment, markdown is supported"
+                            class={classes!(TEXTAREA_CLASSES)}
+                        />
+                        <button type="submit" class={classes!(BUTTON_CLASSES)}>{ "Post" }</button>
+                    </form>
+                }
+                if let Some(e) = &*error {
+                    <p class="error">{ format!("Error: {e}") }</p>
+                }
+            }
+        </div>
+    }
+}
+
+/// Raw HTML is shown as text and only http(s) and mailto links are kept,
+/// so a comment can't run scripts in other reviewers' browsers
+fn markdown(body: &str) -> Html {
+    let safe_url = |url: CowStr<'static>| -> CowStr<'static> {
+        if SAFE_SCHEMES.iter().any(|scheme| url.to_lowercase().starts_with(scheme)) {
+            url
+        } else {
+            CowStr::Borrowed("#")
+        }
+    };
+
+    let events = Parser::new(body).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+            Event::Start(Tag::Link { link_type, dest_url: safe_url(dest_url.into_static()), title, id })
+        }
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+            Event::Start(Tag::Image { link_type, dest_url: safe_url(dest_url.into_static()), title, id })
+        }
+        event => event,
+    });
+
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, events);
+    Html::from_html_unchecked(AttrValue::from(rendered))
+}
+
+/// `2024-05-01T17:30:05.123Z` -> `2024-05-01 17:30`
+fn short_time(time: &str) -> String {
+    time.get(..16).unwrap_or(time).replace('T', " ")
+}
+
+fn history_title(comment: &Comment) -> String {
+    comment.history
+           .iter()
+           .map(|revision| format!("Until {}:\n{}", short_time(&revision.replaced), revision.body))
+           .collect::<Vec<_>>()
+           .join("\n\n")
+}
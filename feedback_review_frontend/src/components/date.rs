@@ -1,8 +1,18 @@
 use yew::prelude::*;
 use yew_router::prelude::*;
+use crate::components::export_buttons::ExportButtons;
+use crate::components::assignment_control::AssignmentControl;
+use crate::components::comment_thread::CommentThread;
 use crate::components::footer::Footer;
-use crate::functions::{get_all_dates, get_feedback_for_date, parse_feedback};
+use crate::components::live_entries::{use_live_entries, EntriesAction};
+use crate::components::tag_editor::{TagEditor, SUGGESTIONS_ID};
+use crate::functions::{
+    delete_entry, get_annotations, get_reviewers, get_tags, set_assignment, set_status, set_tags, Annotation, Comment,
+    Me, TagList, STATUSES, UNAUTHORIZED,
+};
 use crate::Route;
+use std::collections::BTreeMap;
+use web_sys::HtmlSelectElement;
 
 #[derive(Properties, PartialEq)]
 pub struct DateProps {
@@ -15,48 +25,193 @@ pub fn date(props: &DateProps) -> Html {
     gloo::utils::document().set_title(&title);
 
     let date = props.date.clone();
-    let feedback = use_state(|| Err("Loading..".to_string()));
+    let (feedback, live) = use_live_entries(date.clone());
+    let annotations = use_state(BTreeMap::<String, Annotation>::new);
+    // `None` shows every entry
+    let filter = use_state(|| None::<&'static str>);
+    let tag_filter = use_state(|| None::<String>);
+    let tag_list = use_state(TagList::default);
+    let reviewers = use_state(Vec::<String>::new);
+    let annotation_error = use_state(|| None::<String>);
+    let can_moderate = use_context::<Me>().is_some_and(|me| me.can("moderate"));
+    let can_delete = use_context::<Me>().is_some_and(|me| me.can("delete"));
 
     {
-        let feedback = feedback.clone();
+        let annotations = annotations.clone();
+        let annotation_error = annotation_error.clone();
+        let tag_list = tag_list.clone();
+        let reviewers = reviewers.clone();
         let date = date.clone();
 
         use_effect_with((), move |_| {
             wasm_bindgen_futures::spawn_local(async move {
-                let dates = match get_all_dates().await {
-                    Ok(dates) => dates,
-                    Err(err) => {
-                        feedback.set(Err(format!("Unable to get all dates: {err}")));
-                        return;
+                match get_annotations(&date).await {
+                    Ok(loaded) => annotations.set(loaded),
+                    Err(e) => annotation_error.set(Some(e)),
+                }
+                // Only suggestions, editing works without them
+                if let Ok(tags) = get_tags().await {
+                    tag_list.set(tags);
+                }
+                if can_moderate && let Ok(loaded) = get_reviewers().await {
+                    reviewers.set(loaded);
+                }
+            });
+            || ()
+        });
+    }
+
+    let status_of = {
+        let annotations = annotations.clone();
+        move |id: &str| annotations.get(id)
+                                   .map(|annotation| annotation.status.clone())
+                                   .unwrap_or_else(|| String::from("new"))
+    };
+
+    let on_status = |id: String, index: usize| {
+        let annotations = annotations.clone();
+        let annotation_error = annotation_error.clone();
+        let date = date.clone();
+        Callback::from(move |e: Event| {
+            let status = e.target_unchecked_into::<HtmlSelectElement>().value();
+            let annotations = annotations.clone();
+            let annotation_error = annotation_error.clone();
+            let date = date.clone();
+            let id = id.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match set_status(&date, index, &status).await {
+                    Ok(annotation) => {
+                        let mut updated = (*annotations).clone();
+                        updated.insert(id, annotation);
+                        annotations.set(updated);
+                        annotation_error.set(None);
                     }
-                };
+                    Err(e) => annotation_error.set(Some(e)),
+                }
+            });
+        })
+    };
 
-                if !dates.contains(&date) {
-                    feedback.set(Err(format!("Date {date} not found")));
-                    return;
+    let tags_of = {
+        let annotations = annotations.clone();
+        move |id: &str| annotations.get(id)
+                                   .map(|annotation| annotation.tags.clone())
+                                   .unwrap_or_default()
+    };
+
+    let on_tags = |id: String, index: usize| {
+        let annotations = annotations.clone();
+        let annotation_error = annotation_error.clone();
+        let date = date.clone();
+        Callback::from(move |tags: Vec<String>| {
+            let annotations = annotations.clone();
+            let annotation_error = annotation_error.clone();
+            let date = date.clone();
+            let id = id.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match set_tags(&date, index, &tags).await {
+                    Ok(annotation) => {
+                        let mut updated = (*annotations).clone();
+                        updated.insert(id, annotation);
+                        annotations.set(updated);
+                        annotation_error.set(None);
+                    }
+                    Err(e) => annotation_error.set(Some(e)),
                 }
+            });
+        })
+    };
 
-                let raw_feedbacks = match get_feedback_for_date(&date).await {
-                    Ok(f) => f,
-                    Err(e) => {
-                        feedback.set(Err(format!("Unable to get feedback for date {date}: {e}")));
-                        return;
+    let on_assignment = |id: String, index: usize| {
+        let annotations = annotations.clone();
+        let annotation_error = annotation_error.clone();
+        let date = date.clone();
+        Callback::from(move |(assignee, due): (Option<String>, Option<String>)| {
+            let annotations = annotations.clone();
+            let annotation_error = annotation_error.clone();
+            let date = date.clone();
+            let id = id.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match set_assignment(&date, index, assignee.as_deref(), due.as_deref()).await {
+                    Ok(annotation) => {
+                        let mut updated = (*annotations).clone();
+                        updated.insert(id, annotation);
+                        annotations.set(updated);
+                        annotation_error.set(None);
                     }
-                };
+                    Err(e) => annotation_error.set(Some(e)),
+                }
+            });
+        })
+    };
 
-                match parse_feedback(&raw_feedbacks).await {
-                    Ok(fs) => feedback.set(Ok(fs)),
-                    Err(e) => feedback.set(Err(format!("Unable to parse feedback: {e}"))),
+    let comments_of = {
+        let annotations = annotations.clone();
+        move |id: &str| annotations.get(id)
+                                   .map(|annotation| annotation.comments.clone())
+                                   .unwrap_or_default()
+    };
+
+    let on_comments = |id: String| {
+        let annotations = annotations.clone();
+        Callback::from(move |comments: Vec<Comment>| {
+            let mut updated = (*annotations).clone();
+            updated.entry(id.clone()).or_default().comments = comments;
+            annotations.set(updated);
+        })
+    };
+
+    let on_delete = |id: String, index: usize| {
+        let feedback = feedback.dispatcher();
+        let annotations = annotations.clone();
+        let annotation_error = annotation_error.clone();
+        let date = date.clone();
+        Callback::from(move |_| {
+            let Some(reason) = gloo::dialogs::prompt(
+                "Deleting can't be undone. Why is this entry deleted (e.g. the erasure request)?",
+                None,
+            ) else {
+                return;
+            };
+            let feedback = feedback.clone();
+            let annotations = annotations.clone();
+            let annotation_error = annotation_error.clone();
+            let date = date.clone();
+            let id = id.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match delete_entry(&date, index, &reason).await {
+                    Ok(()) => {
+                        feedback.dispatch(EntriesAction::Deleted(id.clone()));
+                        let mut updated = (*annotations).clone();
+                        updated.remove(&id);
+                        annotations.set(updated);
+                        annotation_error.set(None);
+                    }
+                    Err(e) => annotation_error.set(Some(e)),
                 }
             });
-            || ()
-        });
-    }
+        })
+    };
+
+    let on_tag_filter = {
+        let tag_filter = tag_filter.clone();
+        Callback::from(move |tag: String| tag_filter.set(Some(tag)))
+    };
+
+    let on_clear_tag_filter = {
+        let tag_filter = tag_filter.clone();
+        Callback::from(move |_| tag_filter.set(None))
+    };
+
+    let on_filter = |status: Option<&'static str>| {
+        let filter = filter.clone();
+        Callback::from(move |_| filter.set(status))
+    };
 
     html! {
         <>
             {
-                match &*feedback {
+                match &feedback.entries {
                     Ok(feedbacks) => html! {
                         <>
                             <Link<Route> to={Route::Home}>
@@ -70,22 +225,103 @@ pub fn date(props: &DateProps) -> Html {
                             <h1 class={classes!("text-3xl", "font-bold", "mb-6")}>
                                 { format!("Feedback for {date}") }
                             </h1>
+                            if live {
+                                <p class={classes!("mb-4", "text-sm", "text-green-600", "dark:text-green-400")}>
+                                    { "Live, new feedback appears as it arrives" }
+                                </p>
+                            }
+                            <ExportButtons from={date.clone()} to={date.clone()}/>
+                            <div class={classes!("flex", "flex-wrap", "gap-2", "mb-6")}>
+                                {
+                                    for std::iter::once((None, "All"))
+                                        .chain(STATUSES.iter().map(|(status, label)| (Some(*status), *label)))
+                                        .map(|(status, label)| {
+                                            let count = feedbacks.iter()
+                                                                 .filter(|feedback| status.is_none_or(|status| status_of(&feedback.id) == status))
+                                                                 .count();
+                                            html! {
+                                                <button
+                                                    onclick={on_filter(status)}
+                                                    class={classes!(
+                                                        "text-sm", "py-1", "px-3", "rounded",
+                                                        if *filter == status { classes!("bg-indigo-700", "text-white") } else { classes!("bg-gray-200", "hover:bg-gray-300", "dark:bg-gray-700", "dark:hover:bg-gray-600") },
+                                                    )}
+                                                >
+                                                    { format!("{label} ({count})") }
+                                                </button>
+                                            }
+                                        })
+                                }
+                            </div>
+                            if let Some(tag) = &*tag_filter {
+                                <p class={classes!("mb-4", "text-sm")}>
+                                    { format!("Only entries tagged {tag} ") }
+                                    <button onclick={on_clear_tag_filter} class={classes!("text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                                        { "Show all" }
+                                    </button>
+                                </p>
+                            }
+                            <datalist id={SUGGESTIONS_ID}>
+                                { for tag_list.suggestions().into_iter().map(|tag| html! { <option value={tag}/> }) }
+                            </datalist>
+                            if let Some(e) = &*annotation_error {
+                                <p class="error">{ format!("Error: {e}") }</p>
+                            }
                             <ul class={classes!("text-lg", "space-y-4", "w-full", "max-w-4xl")}>
                                 {
                                     for feedbacks
                                         .iter()
+                                        .filter(|feedback| filter.is_none_or(|status| status_of(&feedback.id) == status))
+                                        .filter(|feedback| tag_filter.as_ref().is_none_or(|tag| tags_of(&feedback.id).contains(tag)))
                                         .map(|feedback| html! {
                                             <li class={classes!("flex", "items-center", "p4", "border", "border-gray-200", "rounded-lg", "dark:border-gray-600", "dark:bg-gray-700")}>
                                                 <div class={classes!("flex-1", "feedback-container")}>
                                                     {
-                                                        feedback[1..]
+                                                        for feedback.metadata
                                                             .iter()
+                                                            .map(|(key, values)| html! {
+                                                                <p class={classes!("text-sm", "text-gray-500", "dark:text-gray-400")}>
+                                                                    { format!("({key}: {})", values.join(", ")) }
+                                                                </p>
+                                                            })
+                                                    }
+                                                    {
+                                                        feedback.body
+                                                            .lines()
                                                             .map(|line| html! { <p>{ line }</p> })
                                                             .collect::<Html>()
                                                     }
+                                                    <TagEditor
+                                                        tags={tags_of(&feedback.id)}
+                                                        editable={can_moderate}
+                                                        on_change={on_tags(feedback.id.clone(), feedback.index)}
+                                                        on_select={on_tag_filter.clone()}
+                                                    />
+                                                    <CommentThread
+                                                        date={date.clone()}
+                                                        index={feedback.index}
+                                                        comments={comments_of(&feedback.id)}
+                                                        on_change={on_comments(feedback.id.clone())}
+                                                    />
                                                 </div>
-                                                <div class={classes!("text-sm", "text-gray-500", "dark:text-gray-400", "ml-4")}>
-                                                    { &feedback[0] }
+                                                <div class={classes!("flex", "flex-col", "items-end", "gap-2", "text-sm", "text-gray-500", "dark:text-gray-400", "ml-4")}>
+                                                    { feedback.time().unwrap_or_default() }
+                                                    { status_control(&status_of(&feedback.id), can_moderate, on_status(feedback.id.clone(), feedback.index)) }
+                                                    <AssignmentControl
+                                                        assignment={annotations.get(&feedback.id).and_then(|annotation| annotation.assignment.clone())}
+                                                        done={matches!(status_of(&feedback.id).as_str(), "resolved" | "archived")}
+                                                        editable={can_moderate}
+                                                        reviewers={(*reviewers).clone()}
+                                                        on_change={on_assignment(feedback.id.clone(), feedback.index)}
+                                                    />
+                                                    if can_delete {
+                                                        <button
+                                                            onclick={on_delete(feedback.id.clone(), feedback.index)}
+                                                            class={classes!("text-red-600", "dark:text-red-400", "hover:underline")}
+                                                        >
+                                                            { "Delete" }
+                                                        </button>
+                                                    }
                                                 </div>
                                             </li>
                                         })
@@ -94,9 +330,19 @@ pub fn date(props: &DateProps) -> Html {
                         </>
                 },
                     Err(e) => html! {
-                        <h1 class={classes!("text-3xl", "font-bold", "mb-4")}>
-                            { e }
-                        </h1> },
+                        <>
+                            <h1 class={classes!("text-3xl", "font-bold", "mb-4")}>
+                                { e }
+                            </h1>
+                            if e.ends_with(UNAUTHORIZED) {
+                                <Link<Route> to={Route::Login}>
+                                    <a class={classes!("inline-block", "text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                                        { "Log in" }
+                                    </a>
+                                </Link<Route>>
+                            }
+                        </>
+                    },
                 }
             }
             <Link<Route> to={Route::Home}>
@@ -109,3 +355,25 @@ pub fn date(props: &DateProps) -> Html {
     }
 }
 
+/// A select for moderators, everyone else only sees the status
+fn status_control(current: &str, can_moderate: bool, onchange: Callback<Event>) -> Html {
+    let label = STATUSES.iter()
+                        .find(|(status, _)| *status == current)
+                        .map_or(current, |(_, label)| label);
+
+    if !can_moderate {
+        return html! {
+            <span class={classes!("rounded", "bg-gray-200", "dark:bg-gray-600", "px-2", "py-1")}>{ label }</span>
+        };
+    }
+
+    html! {
+        <select {onchange} class={classes!("rounded", "bg-gray-100", "dark:bg-gray-600", "px-2", "py-1")}>
+            {
+                for STATUSES.iter().map(|(status, label)| html! {
+                    <option value={*status} selected={*status == current}>{ *label }</option>
+                })
+            }
+        </select>
+    }
+}
@@ -0,0 +1,52 @@
+use crate::functions::{download_export, Me};
+use yew::prelude::*;
+
+const FORMATS: [(&str, &str); 4] = [("csv", "CSV"), ("json", "JSON"), ("md", "Markdown"), ("zip", "ZIP")];
+
+#[derive(Properties, PartialEq)]
+pub struct ExportButtonsProps {
+    pub from: String,
+    pub to: String,
+}
+
+/// Only shown to users with the export permission
+#[function_component(ExportButtons)]
+pub fn export_buttons(props: &ExportButtonsProps) -> Html {
+    let me = use_context::<Me>();
+    let error = use_state(|| None::<String>);
+
+    if !me.is_some_and(|me| me.can("export")) {
+        return html! {};
+    }
+
+    let on_export = |format: &'static str| {
+        let (from, to) = (props.from.clone(), props.to.clone());
+        let error = error.clone();
+        Callback::from(move |_| {
+            let (from, to) = (from.clone(), to.clone());
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                error.set(download_export(&from, &to, format).await.err());
+            });
+        })
+    };
+
+    html! {
+        <div class={classes!("flex", "flex-wrap", "items-center", "gap-2", "mb-6")}>
+            <span class={classes!("text-sm", "text-gray-500", "dark:text-gray-400")}>{ "Export" }</span>
+            {
+                for FORMATS.iter().map(|(format, label)| html! {
+                    <button
+                        onclick={on_export(format)}
+                        class={classes!("text-sm", "bg-gray-200", "hover:bg-gray-300", "dark:bg-gray-700", "dark:hover:bg-gray-600", "py-1", "px-3", "rounded")}
+                    >
+                        { *label }
+                    </button>
+                })
+            }
+            if let Some(e) = &*error {
+                <span class="error">{ format!("Error: {e}") }</span>
+            }
+        </div>
+    }
+}
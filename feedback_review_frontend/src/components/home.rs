@@ -1,50 +1,135 @@
 use yew::prelude::*;
 use yew_router::prelude::*;
+use crate::components::export_buttons::ExportButtons;
 use crate::components::footer::Footer;
-use crate::functions::get_all_dates;
+use crate::components::search_bar::SearchBar;
+use crate::functions::{get_dates, time_of_day, DatesPage, Me, UNAUTHORIZED};
 use crate::Route;
 
 #[function_component(Home)]
 pub fn home() -> Html {
-    let dates = use_state(|| None);
+    let dates = use_state(|| None::<Result<DatesPage, String>>);
+    let loading_more = use_state(|| false);
+    let me = use_context::<Me>();
+    let can_present = me.as_ref().is_some_and(|me| me.can("present"));
+    let can_audit = me.is_some_and(|me| me.can("audit"));
 
     {
         let dates = dates.clone();
         use_effect_with((), move |_| {
             wasm_bindgen_futures::spawn_local(async move {
-                let result = get_all_dates().await;
+                let result = get_dates(None).await;
                 dates.set(Some(result));
             });
             || ()
         });
     }
 
+    let on_load_more = {
+        let dates = dates.clone();
+        let loading_more = loading_more.clone();
+        Callback::from(move |_| {
+            let Some(Ok(current)) = (*dates).clone() else { return };
+            let Some(cursor) = current.next_cursor.clone() else { return };
+
+            let dates = dates.clone();
+            let loading_more = loading_more.clone();
+            loading_more.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_dates(Some(&cursor)).await {
+                    Ok(next) => {
+                        let mut all = current.dates;
+                        all.extend(next.dates);
+                        dates.set(Some(Ok(DatesPage { dates: all, next_cursor: next.next_cursor })));
+                    }
+                    Err(e) => dates.set(Some(Err(e))),
+                }
+                loading_more.set(false);
+            });
+        })
+    };
+
     html! {
         <>
             <h1 class={classes!("text-3xl", "font-bold", "mb-6")}>{ "Available Feedback Dates" }</h1>
+            <SearchBar/>
+            <div class={classes!("flex", "gap-4", "mb-6")}>
+                <Link<Route> to={Route::Assignments}>
+                    <a class={classes!("text-blue-500", "dark:text-blue-400", "hover:underline")}>{ "My assignments" }</a>
+                </Link<Route>>
+                <Link<Route> to={Route::Stats}>
+                    <a class={classes!("text-blue-500", "dark:text-blue-400", "hover:underline")}>{ "Statistics" }</a>
+                </Link<Route>>
+                if can_present {
+                    <Link<Route> to={Route::WallQueue}>
+                        <a class={classes!("text-blue-500", "dark:text-blue-400", "hover:underline")}>{ "Feedback wall" }</a>
+                    </Link<Route>>
+                }
+                if can_audit {
+                    <Link<Route> to={Route::AccessLog}>
+                        <a class={classes!("text-blue-500", "dark:text-blue-400", "hover:underline")}>{ "Access log" }</a>
+                    </Link<Route>>
+                }
+            </div>
             {
                 match &*dates {
                     None => html! { <p>{ "Loading..." }</p> },
-                    Some(Ok(dates)) => html! {
+                    Some(Ok(page)) => html! {
                         <>
+                            // Everything loaded so far, "Load more" extends the range
+                            if let (Some(newest), Some(oldest)) = (page.dates.first(), page.dates.last()) {
+                                <ExportButtons from={oldest.date.clone()} to={newest.date.clone()}/>
+                            }
                             <ul class={classes!("space-y-4", "w-full", "max-w-3xl")}>
                                 {
-                                    for dates
+                                    for page.dates
                                         .iter()
-                                        .map(|date| html! {
+                                        .map(|day| html! {
                                             <li>
-                                                <Link<Route> to={Route::Date { date: date.clone() }}>
-                                                    <a class={classes!("block", "w-full", "bg-gray-200", "hover:bg-gray-300", "text-gray-800", "dark:bg-gray-700", "dark:hover:bg-gray-600", "dark:text-gray-300", "font-bold", "py-3", "px-6", "rounded", "shadow", "text-center", "transition")}>
-                                                        { date }
+                                                <Link<Route> to={Route::Date { date: day.date.clone() }}>
+                                                    <a class={classes!("flex", "justify-between", "items-center", "w-full", "bg-gray-200", "hover:bg-gray-300", "text-gray-800", "dark:bg-gray-700", "dark:hover:bg-gray-600", "dark:text-gray-300", "py-3", "px-6", "rounded", "shadow", "transition")}>
+                                                        <span class="font-bold">{ &day.date }</span>
+                                                        <span class={classes!("text-sm", "text-gray-600", "dark:text-gray-400")}>
+                                                            { entries_label(day.count) }
+                                                            {
+                                                                match day.last_entry.as_deref().and_then(time_of_day) {
+                                                                    Some(time) => format!(", last at {time}"),
+                                                                    None => String::new(),
+                                                                }
+                                                            }
+                                                        </span>
                                                     </a>
                                                 </Link<Route>>
                                             </li>
                                         })
                                 }
                             </ul>
+                            if page.dates.is_empty() {
+                                <p>{ "No feedback yet" }</p>
+                            }
+                            if page.next_cursor.is_some() {
+                                <button
+                                    onclick={on_load_more}
+                                    disabled={*loading_more}
+                                    class={classes!("mt-6", "bg-indigo-700", "hover:bg-indigo-800", "text-white", "font-bold", "py-2", "px-4", "rounded")}
+                                >
+                                    { if *loading_more { "Loading..." } else { "Load more" } }
+                                </button>
+                            }
+                        </>
+                    },
+                    Some(Err(err)) => html! {
+                        <>
+                            <p class="error">{ format!("Error: {}", err) }</p>
+                            if err == UNAUTHORIZED {
+                                <Link<Route> to={Route::Login}>
+                                    <a class={classes!("mt-4", "inline-block", "text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                                        { "Log in" }
+                                    </a>
+                                </Link<Route>>
+                            }
                         </>
                     },
-                    Some(Err(err)) => html! { <p class="error">{ format!("Error: {}", err) }</p> },
                 }
             }
             <Footer/>
@@ -52,3 +137,9 @@ pub fn home() -> Html {
     }
 }
 
+fn entries_label(count: usize) -> String {
+    match count {
+        1 => String::from("1 entry"),
+        count => format!("{count} entries"),
+    }
+}
@@ -0,0 +1,146 @@
+use crate::functions::{get_entries, subscribe_entries, today, Entry, RemovedEntry};
+use gloo::events::EventListener;
+use std::cell::Cell;
+use std::rc::Rc;
+use web_sys::wasm_bindgen::JsCast;
+use web_sys::MessageEvent;
+use yew::prelude::*;
+
+/// The entries of a day. Entries that arrive live before the day is loaded wait in `pending`.
+#[derive(Clone, PartialEq)]
+pub struct LiveEntries {
+    pub entries: Result<Vec<Entry>, String>,
+    pending: Vec<Entry>,
+}
+
+pub enum EntriesAction {
+    Loaded(Result<Vec<Entry>, String>),
+    Arrived(Entry),
+    Deleted(String),
+}
+
+impl Reducible for LiveEntries {
+    type Action = EntriesAction;
+
+    fn reduce(self: Rc<Self>, action: EntriesAction) -> Rc<Self> {
+        let mut live = (*self).clone();
+        match (action, &mut live.entries) {
+            (EntriesAction::Loaded(entries), _) => {
+                let pending = std::mem::take(&mut live.pending);
+                live.entries = entries.map(|mut entries| {
+                    for entry in pending {
+                        if !entries.iter().any(|loaded| loaded.id == entry.id) {
+                            entries.push(entry);
+                        }
+                    }
+                    entries.sort_by_key(|entry| entry.index);
+                    entries
+                });
+            }
+            (EntriesAction::Arrived(entry), Ok(entries)) => {
+                if !entries.iter().any(|loaded| loaded.id == entry.id) {
+                    entries.push(entry);
+                }
+            }
+            (EntriesAction::Arrived(entry), Err(_)) => live.pending.push(entry),
+            (EntriesAction::Deleted(id), Ok(entries)) => entries.retain(|entry| entry.id != id),
+            (EntriesAction::Deleted(id), Err(_)) => live.pending.retain(|entry| entry.id != id),
+        }
+        Rc::new(live)
+    }
+}
+
+fn load_entries(entries: UseReducerDispatcher<LiveEntries>, date: String) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let loaded = get_entries(&date).await
+                                       .map_err(|e| format!("Unable to get feedback for date {date}: {e}"));
+        entries.dispatch(EntriesAction::Loaded(loaded));
+    });
+}
+
+/// Loads the entries of `date` and, if it is today, appends new entries as they arrive
+/// and drops the ones deleted meanwhile.
+/// The flag is whether the live connection is up.
+#[hook]
+pub fn use_live_entries(date: String) -> (UseReducerHandle<LiveEntries>, bool) {
+    let entries = use_reducer(|| LiveEntries { entries: Err("Loading..".to_string()), pending: vec![] });
+    let live = use_state(|| false);
+
+    {
+        let entries = entries.dispatcher();
+        let live = live.clone();
+
+        use_effect_with(date, move |date| {
+            let date = date.clone();
+            let mut subscription = None;
+            if date == today() {
+                match subscribe_entries() {
+                    Ok(source) => {
+                        // After a dropped connection the missed entries are only in the day file
+                        let reconnecting = Rc::new(Cell::new(false));
+                        let listeners = [
+                            {
+                                let (entries, date) = (entries.clone(), date.clone());
+                                EventListener::new(&source, "entry", move |event| {
+                                    let Some(data) = event.dyn_ref::<MessageEvent>().and_then(|event| event.data().as_string()) else {
+                                        return;
+                                    };
+                                    match serde_json::from_str::<Entry>(&data) {
+                                        Ok(entry) if entry.date == date => entries.dispatch(EntriesAction::Arrived(entry)),
+                                        Ok(_) => {}
+                                        Err(e) => gloo::console::error!(format!("Invalid entry event: {e}")),
+                                    }
+                                })
+                            },
+                            {
+                                let (entries, date) = (entries.clone(), date.clone());
+                                EventListener::new(&source, "removed", move |event| {
+                                    let Some(data) = event.dyn_ref::<MessageEvent>().and_then(|event| event.data().as_string()) else {
+                                        return;
+                                    };
+                                    match serde_json::from_str::<RemovedEntry>(&data) {
+                                        Ok(removed) if removed.date == date => entries.dispatch(EntriesAction::Deleted(removed.id)),
+                                        Ok(_) => {}
+                                        Err(e) => gloo::console::error!(format!("Invalid removed event: {e}")),
+                                    }
+                                })
+                            },
+                            {
+                                let (entries, date) = (entries.clone(), date.clone());
+                                EventListener::new(&source, "lagged", move |_| load_entries(entries.clone(), date.clone()))
+                            },
+                            {
+                                let (entries, date) = (entries.clone(), date.clone());
+                                let (live, reconnecting) = (live.clone(), reconnecting.clone());
+                                EventListener::new(&source, "open", move |_| {
+                                    live.set(true);
+                                    if reconnecting.replace(false) {
+                                        load_entries(entries.clone(), date.clone());
+                                    }
+                                })
+                            },
+                            {
+                                let live = live.clone();
+                                EventListener::new(&source, "error", move |_| {
+                                    live.set(false);
+                                    reconnecting.set(true);
+                                })
+                            },
+                        ];
+                        subscription = Some((source, listeners));
+                    }
+                    Err(e) => gloo::console::error!(e),
+                }
+            }
+            load_entries(entries, date);
+
+            move || {
+                if let Some((source, _listeners)) = subscription {
+                    source.close();
+                }
+            }
+        });
+    }
+
+    (entries, *live)
+}
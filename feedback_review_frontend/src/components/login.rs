@@ -0,0 +1,136 @@
+use crate::components::footer::Footer;
+use crate::functions::{clear_token, get_me, login, set_token};
+use crate::Route;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+const INPUT_CLASSES: &[&str] = &["w-full", "p-2", "border", "border-gray-300", "rounded", "dark:bg-gray-700", "dark:border-gray-600"];
+const BUTTON_CLASSES: &[&str] = &["w-full", "bg-indigo-700", "hover:bg-indigo-800", "text-white", "font-bold", "py-2", "px-4", "rounded"];
+
+fn bind(state: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let state = state.clone();
+    Callback::from(move |e: InputEvent| {
+        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+            state.set(input.value());
+        }
+    })
+}
+
+#[function_component(Login)]
+pub fn login_page() -> Html {
+    gloo::utils::document().set_title("Feedback Review - Login");
+
+    let navigator = use_navigator().unwrap();
+    let username = use_state(String::new);
+    let password = use_state(String::new);
+    let token = use_state(String::new);
+    let error = use_state(|| None);
+
+    let on_login = {
+        let username = username.clone();
+        let password = password.clone();
+        let error = error.clone();
+        let navigator = navigator.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+
+            let username = username.trim().to_string();
+            let password = (*password).clone();
+            if username.is_empty() || password.is_empty() {
+                error.set(Some(String::from("Please enter username and password")));
+                return;
+            }
+
+            let error = error.clone();
+            let navigator = navigator.clone();
+            // A stale token would take precedence over the new session
+            clear_token();
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match login(&username, &password).await {
+                    Ok(()) => navigator.push(&Route::Home),
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+
+    let on_token = {
+        let token = token.clone();
+        let error = error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+
+            let token = token.trim().to_string();
+            if token.is_empty() {
+                error.set(Some(String::from("Please enter a token")));
+                return;
+            }
+
+            let error = error.clone();
+            let navigator = navigator.clone();
+            set_token(&token);
+
+            // Any authenticated request tells us if the token is valid
+            wasm_bindgen_futures::spawn_local(async move {
+                match get_me().await {
+                    Ok(_) => navigator.push(&Route::Home),
+                    Err(e) => {
+                        clear_token();
+                        error.set(Some(e));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <>
+            <h1 class={classes!("text-3xl", "font-bold", "mb-6")}>{ "Login" }</h1>
+            <form onsubmit={on_login} class={classes!("w-full", "max-w-md", "space-y-4")}>
+                <input
+                    type="text"
+                    placeholder="Username"
+                    autocomplete="username"
+                    value={(*username).clone()}
+                    oninput={bind(&username)}
+                    class={classes!(INPUT_CLASSES)}
+                />
+                <input
+                    type="password"
+                    placeholder="Password"
+                    autocomplete="current-password"
+                    value={(*password).clone()}
+                    oninput={bind(&password)}
+                    class={classes!(INPUT_CLASSES)}
+                />
+                <button type="submit" class={classes!(BUTTON_CLASSES)}>
+                    { "Log in" }
+                </button>
+            </form>
+            <details class={classes!("w-full", "max-w-md", "mt-6")}>
+                <summary class={classes!("cursor-pointer", "text-gray-500", "dark:text-gray-400")}>{ "Use an access token instead" }</summary>
+                <form onsubmit={on_token} class={classes!("space-y-4", "mt-4")}>
+                    <input
+                        type="password"
+                        placeholder="Access token"
+                        value={(*token).clone()}
+                        oninput={bind(&token)}
+                        class={classes!(INPUT_CLASSES)}
+                    />
+                    <button type="submit" class={classes!(BUTTON_CLASSES)}>
+                        { "Use token" }
+                    </button>
+                </form>
+            </details>
+            {
+                match &*error {
+                    Some(e) => html! { <p class={classes!("error", "mt-4")}>{ format!("Error: {e}") }</p> },
+                    None => html! {},
+                }
+            }
+            <Footer/>
+        </>
+    }
+}
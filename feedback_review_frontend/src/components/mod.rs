@@ -3,3 +3,16 @@ pub mod date;
 pub mod version;
 pub mod footer;
 pub mod not_found;
+pub mod login;
+pub mod auth_guard;
+pub mod search;
+pub mod search_bar;
+pub mod export_buttons;
+pub mod stats;
+pub mod tag_editor;
+pub mod comment_thread;
+pub mod assignment_control;
+pub mod assignments;
+pub mod access_log;
+pub mod live_entries;
+pub mod wall;
@@ -0,0 +1,116 @@
+use crate::components::footer::Footer;
+use crate::components::search_bar::SearchBar;
+use crate::functions::{search, time_of_day, SearchParams, SearchResults};
+use crate::Route;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+#[function_component(Search)]
+pub fn search_page() -> Html {
+    gloo::utils::document().set_title("Feedback Review - Search");
+
+    let params = use_location()
+        .and_then(|location| location.query::<SearchParams>().ok())
+        .unwrap_or_default();
+    let results = use_state(|| None::<Result<SearchResults, String>>);
+    let loading_more = use_state(|| false);
+
+    {
+        let results = results.clone();
+        use_effect_with(params.clone(), move |params| {
+            results.set(None);
+            let params = params.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                results.set(Some(search(&params, 0).await));
+            });
+            || ()
+        });
+    }
+
+    let on_load_more = {
+        let results = results.clone();
+        let loading_more = loading_more.clone();
+        let params = params.clone();
+        Callback::from(move |_| {
+            let Some(Ok(current)) = (*results).clone() else { return };
+
+            let results = results.clone();
+            let loading_more = loading_more.clone();
+            let params = params.clone();
+            loading_more.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match search(&params, current.results.len()).await {
+                    Ok(next) => {
+                        let mut all = current.results;
+                        all.extend(next.results);
+                        results.set(Some(Ok(SearchResults { total: next.total, results: all })));
+                    }
+                    Err(e) => results.set(Some(Err(e))),
+                }
+                loading_more.set(false);
+            });
+        })
+    };
+
+    html! {
+        <>
+            <h1 class={classes!("text-3xl", "font-bold", "mb-6")}>{ "Search" }</h1>
+            <SearchBar key={params.q.clone()} initial={params.clone()}/>
+            {
+                match &*results {
+                    None => html! { <p>{ "Searching..." }</p> },
+                    Some(Err(e)) => html! { <p class="error">{ format!("Error: {e}") }</p> },
+                    Some(Ok(found)) => html! {
+                        <>
+                            <p class={classes!("mb-4", "text-gray-500", "dark:text-gray-400")}>
+                                { format!("{} results for {}", found.total, params.q) }
+                            </p>
+                            <ul class={classes!("space-y-4", "w-full", "max-w-4xl")}>
+                                {
+                                    for found.results
+                                        .iter()
+                                        .map(|hit| html! {
+                                            <li key={hit.id.clone()} class={classes!("p-4", "border", "border-gray-200", "rounded-lg", "dark:border-gray-600", "dark:bg-gray-700")}>
+                                                <div class={classes!("flex", "justify-between", "text-sm", "text-gray-500", "dark:text-gray-400", "mb-2")}>
+                                                    <Link<Route> to={Route::Date { date: hit.date.clone() }}>
+                                                        <a class={classes!("text-blue-500", "dark:text-blue-400", "hover:underline")}>{ &hit.date }</a>
+                                                    </Link<Route>>
+                                                    <span>{ hit.timestamp.as_deref().and_then(time_of_day).unwrap_or_default() }</span>
+                                                </div>
+                                                <p>
+                                                    {
+                                                        for hit.snippet
+                                                            .iter()
+                                                            .map(|segment| if segment.highlight {
+                                                                html! { <mark class={classes!("bg-yellow-200", "dark:bg-yellow-600", "rounded")}>{ &segment.text }</mark> }
+                                                            } else {
+                                                                html! { { &segment.text } }
+                                                            })
+                                                    }
+                                                </p>
+                                            </li>
+                                        })
+                                }
+                            </ul>
+                            if found.results.len() < found.total {
+                                <button
+                                    onclick={on_load_more}
+                                    disabled={*loading_more}
+                                    class={classes!("mt-6", "bg-indigo-700", "hover:bg-indigo-800", "text-white", "font-bold", "py-2", "px-4", "rounded")}
+                                >
+                                    { if *loading_more { "Loading..." } else { "Load more" } }
+                                </button>
+                            }
+                        </>
+                    },
+                }
+            }
+            <Link<Route> to={Route::Home}>
+                <a class={classes!("mt-6", "inline-block", "text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                    { "Back to Home" }
+                </a>
+            </Link<Route>>
+            <Footer/>
+        </>
+    }
+}
@@ -0,0 +1,73 @@
+use crate::functions::SearchParams;
+use crate::Route;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+const INPUT_CLASSES: &[&str] = &["p-2", "border", "border-gray-300", "rounded", "dark:bg-gray-700", "dark:border-gray-600"];
+
+#[derive(Properties, PartialEq)]
+pub struct SearchBarProps {
+    #[prop_or_default]
+    pub initial: SearchParams,
+}
+
+fn bind(state: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let state = state.clone();
+    Callback::from(move |e: InputEvent| {
+        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+            state.set(input.value());
+        }
+    })
+}
+
+/// Opens the search page, `"quoted words"` are searched as a phrase
+#[function_component(SearchBar)]
+pub fn search_bar(props: &SearchBarProps) -> Html {
+    let navigator = use_navigator().unwrap();
+    let q = use_state(|| props.initial.q.clone());
+    let from = use_state(|| props.initial.from.clone());
+    let to = use_state(|| props.initial.to.clone());
+    let tag = use_state(|| props.initial.tag.clone());
+
+    let on_submit = {
+        let (q, from, to, tag) = (q.clone(), from.clone(), to.clone(), tag.clone());
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            if q.trim().is_empty() {
+                return;
+            }
+
+            let params = SearchParams {
+                q: q.trim().to_string(),
+                from: (*from).clone(),
+                to: (*to).clone(),
+                tag: tag.trim().to_lowercase(),
+            };
+            if let Err(e) = navigator.push_with_query(&Route::Search, &params) {
+                gloo::console::error!(format!("Failed to open search: {e}"));
+            }
+        })
+    };
+
+    html! {
+        <form onsubmit={on_submit} class={classes!("w-full", "max-w-3xl", "flex", "flex-wrap", "gap-2", "mb-6")}>
+            <input
+                type="search"
+                placeholder={r#"Search feedback, e.g. login "does not work""#}
+                value={(*q).clone()}
+                oninput={bind(&q)}
+                class={classes!("flex-1", "min-w-0", INPUT_CLASSES)}
+            />
+            <input type="date" title="From" value={(*from).clone()} oninput={bind(&from)} class={classes!(INPUT_CLASSES)}/>
+            <input type="date" title="To" value={(*to).clone()} oninput={bind(&to)} class={classes!(INPUT_CLASSES)}/>
+            <input placeholder="Tag" value={(*tag).clone()} oninput={bind(&tag)} class={classes!("w-28", INPUT_CLASSES)}/>
+            <button
+                type="submit"
+                class={classes!("bg-indigo-700", "hover:bg-indigo-800", "text-white", "font-bold", "py-2", "px-4", "rounded")}
+            >
+                { "Search" }
+            </button>
+        </form>
+    }
+}
@@ -0,0 +1,109 @@
+use crate::components::footer::Footer;
+use crate::functions::{get_stats, Bucket, Stats as StatsData};
+use crate::Route;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// How many of the most recent days are charted
+const RECENT_DAYS: usize = 30;
+
+#[function_component(Stats)]
+pub fn stats() -> Html {
+    gloo::utils::document().set_title("Feedback Review - Statistics");
+
+    let stats = use_state(|| None::<Result<StatsData, String>>);
+
+    {
+        let stats = stats.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                stats.set(Some(get_stats().await));
+            });
+            || ()
+        });
+    }
+
+    html! {
+        <>
+            <h1 class={classes!("text-3xl", "font-bold", "mb-6")}>{ "Statistics" }</h1>
+            {
+                match &*stats {
+                    None => html! { <p>{ "Loading..." }</p> },
+                    Some(Err(e)) => html! { <p class="error">{ format!("Error: {e}") }</p> },
+                    Some(Ok(stats)) => html! {
+                        <div class={classes!("w-full", "max-w-4xl", "space-y-8")}>
+                            <div class={classes!("grid", "grid-cols-3", "gap-4")}>
+                                { figure("Entries", stats.total.to_string()) }
+                                { figure("Average length", format!("{:.0} characters", stats.average_length)) }
+                                { figure("Median length", format!("{:.0} characters", stats.median_length)) }
+                            </div>
+                            { chart("Last days", &stats.per_day[stats.per_day.len().saturating_sub(RECENT_DAYS)..]) }
+                            { chart("Per week", &stats.per_week) }
+                            { chart("Per month", &stats.per_month) }
+                            {
+                                chart("By hour of day (UTC)", &stats.by_hour
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(hour, count)| Bucket { key: format!("{hour:02}:00"), count: *count })
+                                    .collect::<Vec<_>>())
+                            }
+                            if !stats.ratings.is_empty() {
+                                {
+                                    chart("Ratings", &stats.ratings
+                                        .iter()
+                                        .map(|(rating, count)| Bucket { key: rating.clone(), count: *count })
+                                        .collect::<Vec<_>>())
+                                }
+                            }
+                        </div>
+                    },
+                }
+            }
+            <Link<Route> to={Route::Home}>
+                <a class={classes!("mt-6", "inline-block", "text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                    { "Back to Home" }
+                </a>
+            </Link<Route>>
+            <Footer/>
+        </>
+    }
+}
+
+fn figure(label: &str, value: String) -> Html {
+    html! {
+        <div class={classes!("p-4", "border", "border-gray-200", "rounded-lg", "dark:border-gray-600", "dark:bg-gray-700")}>
+            <p class={classes!("text-sm", "text-gray-500", "dark:text-gray-400")}>{ label }</p>
+            <p class={classes!("text-2xl", "font-bold")}>{ value }</p>
+        </div>
+    }
+}
+
+/// Horizontal bars, scaled to the largest count
+fn chart(title: &str, buckets: &[Bucket]) -> Html {
+    let max = buckets.iter().map(|bucket| bucket.count).max().unwrap_or(0).max(1);
+
+    html! {
+        <section>
+            <h2 class={classes!("text-xl", "font-bold", "mb-2")}>{ title }</h2>
+            if buckets.is_empty() {
+                <p class={classes!("text-gray-500", "dark:text-gray-400")}>{ "No feedback yet" }</p>
+            }
+            <ul class={classes!("space-y-1")}>
+                {
+                    for buckets.iter().map(|bucket| html! {
+                        <li key={bucket.key.clone()} class={classes!("flex", "items-center", "gap-2", "text-sm")}>
+                            <span class={classes!("w-24", "shrink-0", "text-gray-500", "dark:text-gray-400")}>{ &bucket.key }</span>
+                            <span class={classes!("flex-1")}>
+                                <span
+                                    class={classes!("block", "h-4", "bg-indigo-500", "rounded")}
+                                    style={format!("width: {}%", bucket.count * 100 / max)}
+                                />
+                            </span>
+                            <span class={classes!("w-12", "text-right")}>{ bucket.count }</span>
+                        </li>
+                    })
+                }
+            </ul>
+        </section>
+    }
+}
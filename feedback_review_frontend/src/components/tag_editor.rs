@@ -0,0 +1,79 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The `<datalist>` with the suggestions, rendered once by the page
+pub const SUGGESTIONS_ID: &str = "tag-suggestions";
+
+#[derive(Properties, PartialEq)]
+pub struct TagEditorProps {
+    pub tags: Vec<String>,
+    /// Without it the tags are only shown
+    pub editable: bool,
+    /// The complete new list of tags
+    pub on_change: Callback<Vec<String>>,
+    /// Clicking a tag, e.g. to filter by it
+    #[prop_or_default]
+    pub on_select: Option<Callback<String>>,
+}
+
+#[function_component(TagEditor)]
+pub fn tag_editor(props: &TagEditorProps) -> Html {
+    let input = use_node_ref();
+
+    let on_remove = |tag: String| {
+        let tags = props.tags.clone();
+        let on_change = props.on_change.clone();
+        Callback::from(move |_| {
+            on_change.emit(tags.iter().filter(|other| **other != tag).cloned().collect());
+        })
+    };
+
+    let on_add = {
+        let tags = props.tags.clone();
+        let on_change = props.on_change.clone();
+        let input = input.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let Some(input) = input.cast::<HtmlInputElement>() else { return };
+
+            let tag = input.value().trim().to_lowercase();
+            input.set_value("");
+            if !tag.is_empty() && !tags.contains(&tag) {
+                let mut tags = tags.clone();
+                tags.push(tag);
+                on_change.emit(tags);
+            }
+        })
+    };
+
+    html! {
+        <div class={classes!("flex", "flex-wrap", "items-center", "gap-1", "mt-2", "text-sm")}>
+            {
+                for props.tags.iter().map(|tag| {
+                    let on_select = props.on_select.clone().map(|on_select| {
+                        let tag = tag.clone();
+                        Callback::from(move |_| on_select.emit(tag.clone()))
+                    });
+                    html! {
+                        <span key={tag.clone()} class={classes!("inline-flex", "items-center", "rounded-full", "bg-indigo-100", "text-indigo-800", "dark:bg-indigo-900", "dark:text-indigo-200", "px-2")}>
+                            <button onclick={on_select}>{ tag }</button>
+                            if props.editable {
+                                <button onclick={on_remove(tag.clone())} class="ml-1" title="Remove tag">{ "×" }</button>
+                            }
+                        </span>
+                    }
+                })
+            }
+            if props.editable {
+                <form onsubmit={on_add}>
+                    <input
+                        ref={input}
+                        list={SUGGESTIONS_ID}
+                        placeholder="Add tag"
+                        class={classes!("w-24", "rounded", "bg-gray-100", "dark:bg-gray-600", "px-2")}
+                    />
+                </form>
+            }
+        </div>
+    }
+}
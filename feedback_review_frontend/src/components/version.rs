@@ -1,4 +1,5 @@
 use crate::components::footer::Footer;
+use crate::functions::get_backend_version;
 use crate::VERSION;
 use yew::prelude::*;
 
@@ -6,6 +7,18 @@ use yew::prelude::*;
 pub fn version() -> Html {
     gloo::utils::document().set_title("Feedback Review - Version");
 
+    let backend = use_state(|| None);
+
+    {
+        let backend = backend.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                backend.set(Some(get_backend_version().await));
+            });
+            || ()
+        });
+    }
+
     html! {
         <>
             <h1 class={classes!("text-3xl", "font-bold", "mb-4")}>{ "Application Version" }</h1>
@@ -15,6 +28,24 @@ pub fn version() -> Html {
                     { VERSION }
                 </span>
             </p>
+            {
+                match &*backend {
+                    None => html! { <p class={classes!("text-lg", "mt-4")}>{ "Loading backend version..." }</p> },
+                    Some(Ok(backend)) => html! {
+                        <dl class={classes!("text-lg", "mt-4", "grid", "grid-cols-2", "gap-x-4")}>
+                            <dt>{ "Backend version:" }</dt>
+                            <dd id="backend-version" class={classes!("font-mono")}>{ &backend.version }</dd>
+                            <dt>{ "Git hash:" }</dt>
+                            <dd class={classes!("font-mono")}>{ &backend.git_hash }</dd>
+                            <dt>{ "Build time:" }</dt>
+                            <dd class={classes!("font-mono")}>{ &backend.build_time }</dd>
+                            <dt>{ "Storage mode:" }</dt>
+                            <dd class={classes!("font-mono")}>{ &backend.storage_mode }</dd>
+                        </dl>
+                    },
+                    Some(Err(err)) => html! { <p class="error">{ format!("Error: {}", err) }</p> },
+                }
+            }
             <Footer/>
         </>
     }
@@ -0,0 +1,240 @@
+use crate::components::footer::Footer;
+use crate::components::live_entries::use_live_entries;
+use crate::functions::{get_annotations, set_wall, today, Annotation, Entry, APPROVED, SKIPPED};
+use crate::Route;
+use gloo::timers::callback::Interval;
+use std::collections::BTreeMap;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// Other presenters' decisions show up after at most this long
+const ANNOTATIONS_REFRESH_MS: u32 = 5_000;
+/// The newest approved entries that fit on a projector in large type
+const WALL_SIZE: usize = 5;
+
+/// [`APPROVED`], [`SKIPPED`] or `None` while the entry waits in the queue
+fn approval<'a>(annotations: &'a BTreeMap<String, Annotation>, entry: &Entry) -> Option<&'a str> {
+    annotations.get(&entry.id)
+               .and_then(|annotation| annotation.wall.as_ref())
+               .map(|decision| decision.approval.as_str())
+}
+
+/// Today's annotations, loaded again every few seconds
+#[hook]
+fn use_annotations(date: String) -> (UseStateHandle<BTreeMap<String, Annotation>>, UseStateHandle<Option<String>>) {
+    let annotations = use_state(BTreeMap::<String, Annotation>::new);
+    let error = use_state(|| None::<String>);
+
+    {
+        let annotations = annotations.clone();
+        let error = error.clone();
+        use_effect_with(date, move |date| {
+            let load = {
+                let date = date.clone();
+                move || {
+                    let (annotations, error, date) = (annotations.clone(), error.clone(), date.clone());
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match get_annotations(&date).await {
+                            Ok(loaded) => {
+                                annotations.set(loaded);
+                                error.set(None);
+                            }
+                            Err(e) => error.set(Some(e)),
+                        }
+                    });
+                }
+            };
+            load();
+            let interval = Interval::new(ANNOTATIONS_REFRESH_MS, load);
+            move || drop(interval)
+        });
+    }
+
+    (annotations, error)
+}
+
+/// Today's approved feedback in large type for a projector, newest on top
+#[function_component(Wall)]
+pub fn wall() -> Html {
+    gloo::utils::document().set_title("Feedback Review - Wall");
+
+    let date = today();
+    let (feedback, live) = use_live_entries(date.clone());
+    let (annotations, error) = use_annotations(date);
+
+    let on_fullscreen = Callback::from(|_| {
+        if let Some(root) = gloo::utils::document().document_element()
+            && let Err(e) = root.request_fullscreen()
+        {
+            gloo::console::error!(format!("Failed to enter full screen: {e:?}"));
+        }
+    });
+
+    let shown = match &feedback.entries {
+        Ok(entries) => entries.iter()
+                              .rev()
+                              .filter(|entry| approval(&annotations, entry) == Some(APPROVED))
+                              .take(WALL_SIZE)
+                              .cloned()
+                              .collect::<Vec<_>>(),
+        Err(_) => vec![],
+    };
+
+    html! {
+        <div class={classes!("fixed", "inset-0", "overflow-hidden", "bg-gray-950", "text-gray-100", "p-12", "flex", "flex-col")}>
+            <div class={classes!("flex", "justify-between", "items-center", "text-lg", "text-gray-500", "mb-8")}>
+                <span>
+                    { if live { "Live" } else { "Connecting..." } }
+                    if let Some(e) = &*error {
+                        <span class={classes!("ml-4", "text-red-400")}>{ e }</span>
+                    }
+                </span>
+                <button onclick={on_fullscreen} class={classes!("hover:text-gray-300")}>{ "Full screen" }</button>
+            </div>
+            {
+                match &feedback.entries {
+                    Err(e) => html! { <p class={classes!("text-4xl")}>{ e }</p> },
+                    Ok(_) if shown.is_empty() => html! {
+                        <p class={classes!("text-5xl", "text-gray-500", "m-auto")}>{ "Waiting for feedback..." }</p>
+                    },
+                    Ok(_) => html! {
+                        <ul class={classes!("space-y-10")}>
+                            {
+                                for shown.iter().enumerate().map(|(position, entry)| html! {
+                                    <li
+                                        key={entry.id.clone()}
+                                        class={classes!(
+                                            "border-l-8", "pl-8",
+                                            if position == 0 { classes!("text-6xl", "border-indigo-500") } else { classes!("text-3xl", "border-gray-700", "text-gray-400") },
+                                        )}
+                                    >
+                                        { for entry.body.lines().map(|line| html! { <p>{ line }</p> }) }
+                                    </li>
+                                })
+                            }
+                        </ul>
+                    },
+                }
+            }
+        </div>
+    }
+}
+
+/// Where a presenter approves today's entries for the [`Wall`], oldest waiting entry first.
+/// Decisions are stored with the entry's annotation, so every presenter sees the same queue.
+#[function_component(WallQueue)]
+pub fn wall_queue() -> Html {
+    gloo::utils::document().set_title("Feedback Review - Wall queue");
+
+    let date = today();
+    let (feedback, live) = use_live_entries(date.clone());
+    let (annotations, error) = use_annotations(date.clone());
+
+    let decide = |entry: &Entry, decision: Option<&'static str>| {
+        let annotations = annotations.clone();
+        let error = error.clone();
+        let (date, id, index) = (date.clone(), entry.id.clone(), entry.index);
+        Callback::from(move |_| {
+            let (annotations, error, date, id) = (annotations.clone(), error.clone(), date.clone(), id.clone());
+            wasm_bindgen_futures::spawn_local(async move {
+                match set_wall(&date, index, decision).await {
+                    Ok(annotation) => {
+                        let mut updated = (*annotations).clone();
+                        updated.insert(id, annotation);
+                        annotations.set(updated);
+                        error.set(None);
+                    }
+                    Err(e) => error.set(Some(e)),
+                }
+            });
+        })
+    };
+
+    let entries = feedback.entries.as_ref().map(Vec::as_slice).unwrap_or_default();
+    let with_approval = |wanted: Option<&str>| entries.iter()
+                                                      .filter(|entry| approval(&annotations, entry) == wanted)
+                                                      .collect::<Vec<_>>();
+    let waiting = with_approval(None);
+    let skipped = with_approval(Some(SKIPPED));
+    let mut on_the_wall = with_approval(Some(APPROVED));
+    on_the_wall.reverse();
+
+    let button = |label: &str, onclick: Callback<MouseEvent>, colors: &[&'static str]| html! {
+        <button {onclick} class={classes!("text-sm", "font-bold", "py-1", "px-3", "rounded", colors.to_vec())}>{ label.to_string() }</button>
+    };
+    let heading = |label: String| html! {
+        <h2 class={classes!("text-xl", "font-bold", "mb-2", "self-start", "w-full", "max-w-4xl")}>{ label }</h2>
+    };
+
+    html! {
+        <>
+            <h1 class={classes!("text-3xl", "font-bold", "mb-2")}>{ "Wall queue" }</h1>
+            <p class={classes!("mb-6", "text-gray-500", "dark:text-gray-400")}>
+                { if live { "Live, " } else { "Connecting, " } }
+                <a href="/wall" target="_blank" class={classes!("text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                    { "open the wall" }
+                </a>
+                { " on the projector" }
+            </p>
+            if let Err(e) = &feedback.entries {
+                <p class="error">{ e }</p>
+            }
+            if let Some(e) = &*error {
+                <p class="error">{ format!("Error: {e}") }</p>
+            }
+            { heading(format!("Waiting ({})", waiting.len())) }
+            <ul class={classes!("space-y-2", "w-full", "max-w-4xl", "mb-8")}>
+                {
+                    for waiting.iter().map(|entry| html! {
+                        <li key={entry.id.clone()} class={classes!("flex", "items-center", "gap-4", "p-3", "border", "border-gray-200", "rounded-lg", "dark:border-gray-600")}>
+                            <span class={classes!("text-sm", "text-gray-500", "dark:text-gray-400")}>{ entry.time().unwrap_or_default() }</span>
+                            <div class={classes!("flex-1", "text-lg")}>
+                                { for entry.body.lines().map(|line| html! { <p>{ line }</p> }) }
+                            </div>
+                            { button("Approve", decide(entry, Some(APPROVED)), &["bg-indigo-700", "hover:bg-indigo-800", "text-white"]) }
+                            { button("Skip", decide(entry, Some(SKIPPED)), &["bg-gray-200", "hover:bg-gray-300", "dark:bg-gray-700", "dark:hover:bg-gray-600"]) }
+                        </li>
+                    })
+                }
+            </ul>
+            { heading(format!("On the wall ({})", on_the_wall.len())) }
+            <ul class={classes!("space-y-2", "w-full", "max-w-4xl", "mb-8")}>
+                {
+                    for on_the_wall.iter().enumerate().map(|(position, entry)| html! {
+                        <li
+                            key={entry.id.clone()}
+                            class={classes!(
+                                "flex", "items-center", "gap-4", "p-3", "border", "border-gray-200", "rounded-lg", "dark:border-gray-600",
+                                (position >= WALL_SIZE).then_some("opacity-50"),
+                            )}
+                        >
+                            <div class={classes!("flex-1")}>
+                                { for entry.body.lines().map(|line| html! { <p>{ line }</p> }) }
+                            </div>
+                            { button("Remove", decide(entry, Some(SKIPPED)), &["text-red-600", "dark:text-red-400", "hover:underline"]) }
+                        </li>
+                    })
+                }
+            </ul>
+            { heading(format!("Skipped ({})", skipped.len())) }
+            <ul class={classes!("space-y-2", "w-full", "max-w-4xl")}>
+                {
+                    for skipped.iter().map(|entry| html! {
+                        <li key={entry.id.clone()} class={classes!("flex", "items-center", "gap-4", "p-3", "border", "border-gray-200", "rounded-lg", "dark:border-gray-600", "opacity-50")}>
+                            <div class={classes!("flex-1")}>
+                                { for entry.body.lines().map(|line| html! { <p>{ line }</p> }) }
+                            </div>
+                            { button("Back to queue", decide(entry, None), &["text-blue-500", "dark:text-blue-400", "hover:underline"]) }
+                        </li>
+                    })
+                }
+            </ul>
+            <Link<Route> to={Route::Home}>
+                <a class={classes!("mt-6", "inline-block", "text-blue-500", "dark:text-blue-400", "hover:underline")}>
+                    { "Back to Home" }
+                </a>
+            </Link<Route>>
+            <Footer/>
+        </>
+    }
+}
@@ -1,76 +1,874 @@
 use crate::BACKEND_URL;
-use gloo::net::http::Request;
-use regex::Regex;
-use serde::Deserialize;
+use gloo::net::http::{Request, RequestBuilder};
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use gloo::file::{Blob, ObjectUrl};
+use gloo::timers::callback::Timeout;
+use web_sys::wasm_bindgen::JsCast;
+use web_sys::{EventSource, EventSourceInit, HtmlElement, RequestCredentials};
 
+/// Sent by the backend with 400, e.g. if a date isn't `YYYY-MM-DD`
 #[derive(Debug, Deserialize)]
-struct FeedbackResponse {
-    feedback: Option<String>,
+struct InvalidInput {
+    message: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct FeedbackDates {
-    dates: Option<Vec<String>>,
+    dates: Option<Vec<DaySummary>>,
+    next_cursor: Option<String>,
 }
-pub async fn get_all_dates() -> Result<Vec<String>, String> {
-    let target_url = format!("{BACKEND_URL}/dates");
-    let res = Request::get(&target_url)
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DaySummary {
+    pub date: String,
+    pub count: usize,
+    pub last_entry: Option<String>,
+}
+
+/// A single feedback entry, parsed by the backend
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Entry {
+    pub id: String,
+    pub date: String,
+    pub index: usize,
+    pub timestamp: Option<String>,
+    pub body: String,
+    pub metadata: BTreeMap<String, Vec<String>>,
+}
+
+/// An entry that was deleted while the page was open
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RemovedEntry {
+    pub id: String,
+    pub date: String,
+}
+
+impl Entry {
+    pub fn time(&self) -> Option<&str> {
+        self.timestamp.as_deref().and_then(time_of_day)
+    }
+}
+
+thread_local! {
+    /// How far the backend's clock is ahead of the browser's, measured when `/me` was loaded
+    static CLOCK_OFFSET_MS: Cell<f64> = const { Cell::new(0.0) };
+}
+
+/// `YYYY-MM-DD` of the day new feedback goes to. The day files are named by the UTC date
+/// on the server, so the browser's clock is corrected by the offset to the backend's clock.
+pub fn today() -> String {
+    let now = js_sys::Date::now() + CLOCK_OFFSET_MS.get();
+    String::from(js_sys::Date::new(&now.into()).to_iso_string()).chars().take(10).collect()
+}
+
+/// `2024-05-01T17:30:05Z` -> `17:30:05`, times are UTC like in the day files
+pub fn time_of_day(time: &str) -> Option<&str> {
+    time.split_once('T')
+        .map(|(_, time)| time.trim_end_matches('Z'))
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SearchResults {
+    pub total: usize,
+    pub results: Vec<SearchHit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SearchHit {
+    pub id: String,
+    pub date: String,
+    pub timestamp: Option<String>,
+    pub snippet: Vec<SnippetSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SnippetSegment {
+    pub text: String,
+    pub highlight: bool,
+}
+
+/// Also the query string of the search page, so results can be linked
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub from: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub to: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub tag: String,
+}
+
+/// The triage states in workflow order, `new` is every entry nobody annotated yet
+pub const STATUSES: [(&str, &str); 5] = [
+    ("new", "New"),
+    ("read", "Read"),
+    ("in_progress", "In progress"),
+    ("resolved", "Resolved"),
+    ("archived", "Archived"),
+];
+
+/// A presenter put the entry on the projector wall
+pub const APPROVED: &str = "approved";
+/// A presenter left the entry out of the wall, or took it off again
+pub const SKIPPED: &str = "skipped";
+
+/// What reviewers recorded about an entry, stored by the review backend
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Annotation {
+    pub status: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    pub assignment: Option<Assignment>,
+    /// `None` while the entry waits in the wall queue
+    pub wall: Option<WallDecision>,
+    pub updated: Option<Change>,
+}
+
+/// Like an entry nobody annotated yet
+impl Default for Annotation {
+    fn default() -> Self {
+        Self {
+            status: String::from("new"),
+            tags: vec![],
+            comments: vec![],
+            assignment: None,
+            wall: None,
+            updated: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Assignment {
+    pub assignee: String,
+    /// `YYYY-MM-DD`
+    pub due: Option<String>,
+    pub by: String,
+    pub at: String,
+}
+
+impl Assignment {
+    /// Past the due date, whether the entry is done is up to the caller
+    pub fn past_due(&self) -> bool {
+        self.due.as_deref().is_some_and(|due| due < today().as_str())
+    }
+}
+
+/// An entry assigned to the logged in reviewer
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AssignedEntry {
+    pub entry: Entry,
+    pub annotation: Annotation,
+    pub overdue: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Comment {
+    pub id: usize,
+    pub author: String,
+    pub created: String,
+    /// Markdown
+    pub body: String,
+    /// Earlier bodies, oldest first
+    #[serde(default)]
+    pub history: Vec<Revision>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Revision {
+    pub body: String,
+    pub replaced: String,
+}
+
+/// Tags to suggest when editing, the predefined ones first
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct TagList {
+    pub predefined: Vec<String>,
+    pub used: BTreeMap<String, usize>,
+}
+
+impl TagList {
+    pub fn suggestions(&self) -> Vec<String> {
+        let mut suggestions = self.predefined.clone();
+        suggestions.extend(self.used
+                               .keys()
+                               .filter(|tag| !self.predefined.contains(tag))
+                               .cloned());
+        suggestions
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WallDecision {
+    /// [`APPROVED`] or [`SKIPPED`]
+    pub approval: String,
+    pub by: String,
+    pub at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Change {
+    pub by: String,
+    pub at: String,
+}
+
+#[derive(Debug, Serialize)]
+struct StatusUpdate<'a> {
+    status: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct AssignmentUpdate<'a> {
+    assignee: Option<&'a str>,
+    due: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct DeleteRequest<'a> {
+    reason: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct CommentBody<'a> {
+    body: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct TagsUpdate<'a> {
+    tags: &'a [String],
+}
+
+#[derive(Debug, Serialize)]
+struct WallUpdate<'a> {
+    approval: Option<&'a str>,
+}
+
+/// Aggregated over every day file, computed by the backend
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Stats {
+    pub total: usize,
+    pub per_day: Vec<Bucket>,
+    pub per_week: Vec<Bucket>,
+    pub per_month: Vec<Bucket>,
+    pub by_hour: Vec<usize>,
+    pub average_length: f64,
+    pub median_length: f64,
+    pub ratings: BTreeMap<String, usize>,
+}
+
+/// A day (`2024-05-01`), ISO week (`2024-W18`) or month (`2024-05`) and its count
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Bucket {
+    pub key: String,
+    pub count: usize,
+}
+
+/// One authenticated request to the review backend
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AccessEvent {
+    pub at: String,
+    pub user: String,
+    pub method: String,
+    pub route: String,
+    pub params: BTreeMap<String, String>,
+    pub status: u16,
+    pub size: u64,
+}
+
+/// Newest first, `total` counts every match
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AccessPage {
+    pub events: Vec<AccessEvent>,
+    pub total: usize,
+}
+
+/// Empty fields don't filter, `day` finds requests for that day of feedback
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccessFilter {
+    pub user: String,
+    pub route: String,
+    pub day: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// One page of days, newest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatesPage {
+    pub dates: Vec<DaySummary>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Credentials<'a> {
+    username: &'a str,
+    password: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Me {
+    pub name: String,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    /// The backend's clock when it answered
+    pub now: String,
+}
+
+impl Me {
+    /// Controls the user can't use are hidden, the backend enforces the permissions anyway
+    pub fn can(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|granted| granted == permission)
+    }
+}
+
+const TOKEN_KEY: &str = "review_token";
+pub const UNAUTHORIZED: &str = "Not logged in or the token is no longer valid";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BackendVersion {
+    pub name: String,
+    pub version: String,
+    pub git_hash: String,
+    pub build_time: String,
+    pub storage_mode: String,
+}
+
+pub fn get_token() -> Option<String> {
+    LocalStorage::get(TOKEN_KEY).ok()
+}
+
+pub fn set_token(token: &str) {
+    if let Err(e) = LocalStorage::set(TOKEN_KEY, token) {
+        gloo::console::error!(format!("Failed to store token: {e}"));
+    }
+}
+
+pub fn clear_token() {
+    LocalStorage::delete(TOKEN_KEY);
+}
+
+/// Sends the session cookie, and the token if one was entered
+fn authorized(req: RequestBuilder) -> RequestBuilder {
+    let req = req.credentials(RequestCredentials::Include);
+    match get_token() {
+        Some(token) => req.header("Authorization", &format!("Bearer {token}")),
+        None => req,
+    }
+}
+
+pub async fn login(username: &str, password: &str) -> Result<(), String> {
+    let target_url = format!("{BACKEND_URL}/login");
+
+    let res = Request::post(&target_url)
+        .credentials(RequestCredentials::Include)
+        .json(&Credentials { username, password })
+        .map_err(|e| format!("Failed to serialize credentials: {e}"))?
         .send()
         .await
         .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
 
+    match res.status() {
+        200 => Ok(()),
+        401 => Err(String::from("Invalid username or password")),
+        status => Err(format!("Login failed with status {status}")),
+    }
+}
+
+pub async fn logout() -> Result<(), String> {
+    let target_url = format!("{BACKEND_URL}/logout");
+    clear_token();
+
+    let res = Request::post(&target_url)
+        .credentials(RequestCredentials::Include)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    if !res.ok() {
+        return Err(format!("Logout failed with status {}", res.status()));
+    }
+
+    Ok(())
+}
+
+pub async fn get_me() -> Result<Me, String> {
+    let target_url = format!("{BACKEND_URL}/me");
+
+    let res = authorized(Request::get(&target_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    if res.status() == 401 {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    let me = res.json::<Me>()
+                .await
+                .map_err(|e| format!("Unable to parse response as JSON: {e}"))?;
+
+    let server_now = js_sys::Date::parse(&me.now);
+    if !server_now.is_nan() {
+        CLOCK_OFFSET_MS.set(server_now - js_sys::Date::now());
+    }
+
+    Ok(me)
+}
+
+pub async fn get_dates(cursor: Option<&str>) -> Result<DatesPage, String> {
+    let target_url = match cursor {
+        Some(cursor) => format!("{BACKEND_URL}/dates?cursor={cursor}"),
+        None => format!("{BACKEND_URL}/dates"),
+    };
+    let res = authorized(Request::get(&target_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    if res.status() == 401 {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
     let dates = res
         .json::<FeedbackDates>()
         .await
         .map_err(|e| format!("Unable to parse response as JSON: {e}"))?;
 
-    dates.dates.ok_or_else(|| "No dates found".to_string())
+    Ok(DatesPage {
+        dates: dates.dates.ok_or_else(|| "No dates found".to_string())?,
+        next_cursor: dates.next_cursor,
+    })
 }
 
-pub async fn get_feedback_for_date(date: &str) -> Result<String, String> {
-    let target_url = format!("{BACKEND_URL}/feedback/{date}");
+pub async fn get_entries(date: &str) -> Result<Vec<Entry>, String> {
+    let target_url = format!("{BACKEND_URL}/feedback/{date}/entries");
 
-    let res = Request::get(&target_url)
+    let res = authorized(Request::get(&target_url))
         .send()
         .await
         .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
 
-    let res = res
-        .json::<FeedbackResponse>()
+    if res.status() == 401 {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    if res.status() == 400 {
+        let rejection = res
+            .json::<InvalidInput>()
+            .await
+            .map_err(|e| format!("Unable to parse response as JSON: {e}"))?;
+        return Err(format!("Invalid date {date}: {}", rejection.message));
+    }
+
+    let request_id = res.headers()
+                        .get("x-request-id")
+                        .unwrap_or_else(|| String::from("unknown"));
+
+    if res.status() == 404 {
+        return Err(format!("No feedback found for date {date} (request id {request_id})"));
+    }
+
+    res.json::<Vec<Entry>>()
+       .await
+       .map_err(|e| format!("Unable to parse response as JSON (request id {request_id}): {e}"))
+}
+
+/// New entries as `entry` events, deleted ones as `removed` events, `lagged` if some were missed.
+/// `EventSource` can't send the token, only logins with the session cookie get live updates.
+pub fn subscribe_entries() -> Result<EventSource, String> {
+    let target_url = format!("{BACKEND_URL}/events");
+    let init = EventSourceInit::new();
+    init.set_with_credentials(true);
+
+    EventSource::new_with_event_source_init_dict(&target_url, &init)
+        .map_err(|e| format!("Failed to connect to {target_url}: {e:?}"))
+}
+
+pub async fn search(params: &SearchParams, offset: usize) -> Result<SearchResults, String> {
+    let target_url = format!("{BACKEND_URL}/search");
+    let offset = offset.to_string();
+
+    let mut query = vec![("q", params.q.as_str()), ("offset", offset.as_str())];
+    if !params.from.is_empty() {
+        query.push(("from", params.from.as_str()));
+    }
+    if !params.to.is_empty() {
+        query.push(("to", params.to.as_str()));
+    }
+    if !params.tag.is_empty() {
+        query.push(("tag", params.tag.as_str()));
+    }
+
+    let res = authorized(Request::get(&target_url))
+        .query(query)
+        .send()
         .await
-        .map_err(|e| format!("Unable to parse response {res:?} as JSON: {e}"))?;
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
 
-    res.feedback.ok_or_else(|| format!("No feedback found for date {date}"))
+    if res.status() == 401 {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    if res.status() == 400 {
+        let rejection = res
+            .json::<InvalidInput>()
+            .await
+            .map_err(|e| format!("Unable to parse response as JSON: {e}"))?;
+        return Err(rejection.message);
+    }
+
+    res.json::<SearchResults>()
+       .await
+       .map_err(|e| format!("Unable to parse response as JSON: {e}"))
 }
 
-pub async fn parse_feedback(feedback: &str) -> Result<Vec<Vec<String>>, String> {
-    const DASH_CNT: usize = 50;
-    let feedback_time_regex = Regex::new(r"^\[\d{4}-\d{2}-\d{2} - (\d{2}:\d{2}:\d{2})]z$").unwrap();
+/// Keyed by entry id, entries without an annotation are missing
+pub async fn get_annotations(date: &str) -> Result<BTreeMap<String, Annotation>, String> {
+    let target_url = format!("{BACKEND_URL}/feedback/{date}/annotations");
 
-    let mut feedbacks = vec![];
-    let mut curr_lines = vec![];
-    let mut active = false;
+    let res = authorized(Request::get(&target_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
 
-    for line in feedback.lines() {
-        if line == "-".repeat(DASH_CNT) {
-            active = !active;
-            if !active { // => Just turned inactive
-                feedbacks.push(curr_lines);
-                curr_lines = vec![];
-            } else {
-                continue;
-            }
-        }
+    if res.status() == 401 {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    if !res.ok() {
+        return Err(format!("Failed to load annotations: {}", res.status()));
+    }
+
+    res.json::<BTreeMap<String, Annotation>>()
+       .await
+       .map_err(|e| format!("Unable to parse response as JSON: {e}"))
+}
+
+pub async fn set_status(date: &str, index: usize, status: &str) -> Result<Annotation, String> {
+    let target_url = format!("{BACKEND_URL}/feedback/{date}/entries/{index}/status");
+
+    let res = authorized(Request::put(&target_url))
+        .json(&StatusUpdate { status })
+        .map_err(|e| format!("Failed to serialize status: {e}"))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    match res.status() {
+        200 => res.json::<Annotation>()
+                  .await
+                  .map_err(|e| format!("Unable to parse response as JSON: {e}")),
+        401 => Err(UNAUTHORIZED.to_string()),
+        403 => Err(String::from("Missing the permission to change the status")),
+        status => Err(format!("Failed to change the status: {status}")),
+    }
+}
+
+pub async fn set_tags(date: &str, index: usize, tags: &[String]) -> Result<Annotation, String> {
+    let target_url = format!("{BACKEND_URL}/feedback/{date}/entries/{index}/tags");
+
+    let res = authorized(Request::put(&target_url))
+        .json(&TagsUpdate { tags })
+        .map_err(|e| format!("Failed to serialize tags: {e}"))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    match res.status() {
+        200 => res.json::<Annotation>()
+                  .await
+                  .map_err(|e| format!("Unable to parse response as JSON: {e}")),
+        400 => Err(res.json::<InvalidInput>()
+                      .await
+                      .map(|rejection| rejection.message)
+                      .unwrap_or_else(|e| format!("Unable to parse response as JSON: {e}"))),
+        401 => Err(UNAUTHORIZED.to_string()),
+        403 => Err(String::from("Missing the permission to change tags")),
+        status => Err(format!("Failed to change the tags: {status}")),
+    }
+}
+
+/// [`APPROVED`], [`SKIPPED`] or `None` to put the entry back into the queue
+pub async fn set_wall(date: &str, index: usize, approval: Option<&str>) -> Result<Annotation, String> {
+    let target_url = format!("{BACKEND_URL}/feedback/{date}/entries/{index}/wall");
+
+    let res = authorized(Request::put(&target_url))
+        .json(&WallUpdate { approval })
+        .map_err(|e| format!("Failed to serialize approval: {e}"))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    match res.status() {
+        200 => res.json::<Annotation>()
+                  .await
+                  .map_err(|e| format!("Unable to parse response as JSON: {e}")),
+        401 => Err(UNAUTHORIZED.to_string()),
+        403 => Err(String::from("Missing the permission to present")),
+        404 => Err(String::from("The entry was deleted")),
+        status => Err(format!("Failed to change the wall: {status}")),
+    }
+}
+
+pub async fn add_comment(date: &str, index: usize, body: &str) -> Result<Comment, String> {
+    let target_url = format!("{BACKEND_URL}/feedback/{date}/entries/{index}/comments");
+    send_comment(authorized(Request::post(&target_url)), &target_url, body).await
+}
+
+/// Only works for the author's own comments
+pub async fn edit_comment(date: &str, index: usize, id: usize, body: &str) -> Result<Comment, String> {
+    let target_url = format!("{BACKEND_URL}/feedback/{date}/entries/{index}/comments/{id}");
+    send_comment(authorized(Request::put(&target_url)), &target_url, body).await
+}
+
+async fn send_comment(req: RequestBuilder, target_url: &str, body: &str) -> Result<Comment, String> {
+    let res = req.json(&CommentBody { body })
+                 .map_err(|e| format!("Failed to serialize comment: {e}"))?
+                 .send()
+                 .await
+                 .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    match res.status() {
+        200 | 201 => res.json::<Comment>()
+                        .await
+                        .map_err(|e| format!("Unable to parse response as JSON: {e}")),
+        400 => Err(res.json::<InvalidInput>()
+                      .await
+                      .map(|rejection| rejection.message)
+                      .unwrap_or_else(|e| format!("Unable to parse response as JSON: {e}"))),
+        401 => Err(UNAUTHORIZED.to_string()),
+        403 => Err(String::from("Not allowed to change this comment")),
+        status => Err(format!("Failed to save the comment: {status}")),
+    }
+}
+
+/// `None` removes the assignment
+pub async fn set_assignment(date: &str, index: usize, assignee: Option<&str>, due: Option<&str>) -> Result<Annotation, String> {
+    let target_url = format!("{BACKEND_URL}/feedback/{date}/entries/{index}/assignment");
+
+    let res = authorized(Request::put(&target_url))
+        .json(&AssignmentUpdate { assignee, due })
+        .map_err(|e| format!("Failed to serialize assignment: {e}"))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    match res.status() {
+        200 => res.json::<Annotation>()
+                  .await
+                  .map_err(|e| format!("Unable to parse response as JSON: {e}")),
+        400 => Err(res.json::<InvalidInput>()
+                      .await
+                      .map(|rejection| rejection.message)
+                      .unwrap_or_else(|e| format!("Unable to parse response as JSON: {e}"))),
+        401 => Err(UNAUTHORIZED.to_string()),
+        403 => Err(String::from("Missing the permission to assign entries")),
+        status => Err(format!("Failed to change the assignment: {status}")),
+    }
+}
+
+/// Leaves a tombstone in the day file, `reason` goes into the audit log
+pub async fn delete_entry(date: &str, index: usize, reason: &str) -> Result<(), String> {
+    let target_url = format!("{BACKEND_URL}/feedback/{date}/entries/{index}");
+
+    let res = authorized(Request::delete(&target_url))
+        .json(&DeleteRequest { reason })
+        .map_err(|e| format!("Failed to serialize deletion: {e}"))?
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    match res.status() {
+        200 => Ok(()),
+        400 => Err(res.json::<InvalidInput>()
+                      .await
+                      .map(|rejection| rejection.message)
+                      .unwrap_or_else(|e| format!("Unable to parse response as JSON: {e}"))),
+        401 => Err(UNAUTHORIZED.to_string()),
+        403 => Err(String::from("Missing the permission to delete entries")),
+        404 => Err(String::from("The entry was deleted already")),
+        status => Err(format!("Failed to delete the entry: {status}")),
+    }
+}
+
+/// Reviewer accounts that entries can be assigned to
+pub async fn get_reviewers() -> Result<Vec<String>, String> {
+    let target_url = format!("{BACKEND_URL}/reviewers");
+
+    let res = authorized(Request::get(&target_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    if !res.ok() {
+        return Err(format!("Failed to load reviewers: {}", res.status()));
+    }
+
+    res.json::<Vec<String>>()
+       .await
+       .map_err(|e| format!("Unable to parse response as JSON: {e}"))
+}
+
+/// `done` includes resolved and archived entries
+pub async fn get_my_assignments(done: bool) -> Result<Vec<AssignedEntry>, String> {
+    let target_url = format!("{BACKEND_URL}/me/assignments");
 
-        if !active { continue; }
+    let res = authorized(Request::get(&target_url))
+        .query([("done", if done { "true" } else { "false" })])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    if res.status() == 401 {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    if !res.ok() {
+        return Err(format!("Failed to load assignments: {}", res.status()));
+    }
 
-        if let Some(capture) = feedback_time_regex.captures(line) {
-            curr_lines.push(capture[1].to_string());
-        } else {
-            curr_lines.push(line.to_string());
+    res.json::<Vec<AssignedEntry>>()
+       .await
+       .map_err(|e| format!("Unable to parse response as JSON: {e}"))
+}
+
+pub async fn get_tags() -> Result<TagList, String> {
+    let target_url = format!("{BACKEND_URL}/tags");
+
+    let res = authorized(Request::get(&target_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    if res.status() == 401 {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    if !res.ok() {
+        return Err(format!("Failed to load tags: {}", res.status()));
+    }
+
+    res.json::<TagList>()
+       .await
+       .map_err(|e| format!("Unable to parse response as JSON: {e}"))
+}
+
+pub async fn get_stats() -> Result<Stats, String> {
+    let target_url = format!("{BACKEND_URL}/stats");
+
+    let res = authorized(Request::get(&target_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    if res.status() == 401 {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    let request_id = res.headers()
+                        .get("x-request-id")
+                        .unwrap_or_else(|| String::from("unknown"));
+
+    if !res.ok() {
+        return Err(format!("Failed to load statistics: {} (request id {request_id})", res.status()));
+    }
+
+    res.json::<Stats>()
+       .await
+       .map_err(|e| format!("Unable to parse response as JSON (request id {request_id}): {e}"))
+}
+
+pub async fn get_access_log(filter: &AccessFilter, offset: usize) -> Result<AccessPage, String> {
+    let target_url = format!("{BACKEND_URL}/audit/access");
+    let offset = offset.to_string();
+
+    let mut query = vec![("offset", offset.as_str())];
+    for (key, value) in [
+        ("user", &filter.user),
+        ("route", &filter.route),
+        ("day", &filter.day),
+        ("from", &filter.from),
+        ("to", &filter.to),
+    ] {
+        if !value.is_empty() {
+            query.push((key, value.as_str()));
         }
     }
 
-    Ok(feedbacks)
+    let res = authorized(Request::get(&target_url))
+        .query(query)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    match res.status() {
+        200 => res.json::<AccessPage>()
+                  .await
+                  .map_err(|e| format!("Unable to parse response as JSON: {e}")),
+        400 => Err(res.json::<InvalidInput>()
+                      .await
+                      .map(|rejection| rejection.message)
+                      .unwrap_or_else(|e| format!("Unable to parse response as JSON: {e}"))),
+        401 => Err(UNAUTHORIZED.to_string()),
+        403 => Err(String::from("Missing the permission to read the access log")),
+        status => Err(format!("Failed to load the access log: {status}")),
+    }
+}
+
+/// Downloads an export through `fetch`, so the token is sent along, and saves it as a file.
+/// `format` is one of `csv`, `json`, `md` or `zip`.
+pub async fn download_export(from: &str, to: &str, format: &str) -> Result<(), String> {
+    let target_url = format!("{BACKEND_URL}/export");
+
+    let res = authorized(Request::get(&target_url))
+        .query([("from", from), ("to", to), ("format", format)])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    match res.status() {
+        200 => {}
+        401 => return Err(UNAUTHORIZED.to_string()),
+        403 => return Err(String::from("You may not export feedback")),
+        status => return Err(format!("Export failed with status {status}")),
+    }
+
+    let content_type = res.headers().get("content-type");
+    let bytes = res
+        .binary()
+        .await
+        .map_err(|e| format!("Failed to read export: {e}"))?;
+    let url = ObjectUrl::from(Blob::new_with_options(bytes.as_slice(), content_type.as_deref()));
+
+    let link = gloo::utils::document()
+        .create_element("a")
+        .map_err(|e| format!("Failed to create download link: {e:?}"))?;
+    link.set_attribute("href", &url)
+        .and_then(|_| link.set_attribute("download", &format!("feedback-{from}-{to}.{format}")))
+        .map_err(|e| format!("Failed to create download link: {e:?}"))?;
+    link.unchecked_into::<HtmlElement>().click();
+
+    // Revoking the URL right away can cancel the download in some browsers
+    Timeout::new(10_000, move || drop(url)).forget();
+
+    Ok(())
 }
 
+pub async fn get_backend_version() -> Result<BackendVersion, String> {
+    let target_url = format!("{BACKEND_URL}/version");
+
+    let res = Request::get(&target_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send request to {target_url}: {e}"))?;
+
+    res.json::<BackendVersion>()
+       .await
+       .map_err(|e| format!("Unable to parse response as JSON: {e}"))
+}
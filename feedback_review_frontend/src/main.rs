@@ -1,7 +1,14 @@
+use crate::components::access_log::AccessLog;
+use crate::components::assignments::Assignments;
+use crate::components::auth_guard::AuthGuard;
 use crate::components::date::Date;
 use crate::components::home::Home;
+use crate::components::login::Login;
 use crate::components::not_found::NotFound;
+use crate::components::search::Search;
+use crate::components::stats::Stats;
 use crate::components::version::Version;
+use crate::components::wall::{Wall, WallQueue};
 use yew::prelude::*;
 use yew_router::prelude::*;
 
@@ -17,6 +24,20 @@ enum Route {
     Home,
     #[at("/version")]
     Version,
+    #[at("/login")]
+    Login,
+    #[at("/search")]
+    Search,
+    #[at("/stats")]
+    Stats,
+    #[at("/assignments")]
+    Assignments,
+    #[at("/access-log")]
+    AccessLog,
+    #[at("/wall")]
+    Wall,
+    #[at("/wall/queue")]
+    WallQueue,
     #[at("/:date")]
     Date { date: String },
     #[not_found]
@@ -26,9 +47,16 @@ enum Route {
 
 fn switch(routes: Route) -> Html {
     match routes {
-        Route::Home => html! { <Home/> },
-        Route::Date { date } => html! { <Date date={date}/>},
+        Route::Home => html! { <AuthGuard><Home/></AuthGuard> },
+        Route::Date { date } => html! { <AuthGuard><Date date={date}/></AuthGuard> },
         Route::Version => html! { <Version/>},
+        Route::Login => html! { <Login/> },
+        Route::Search => html! { <AuthGuard><Search/></AuthGuard> },
+        Route::Stats => html! { <AuthGuard><Stats/></AuthGuard> },
+        Route::Assignments => html! { <AuthGuard><Assignments/></AuthGuard> },
+        Route::AccessLog => html! { <AuthGuard permission="audit"><AccessLog/></AuthGuard> },
+        Route::Wall => html! { <AuthGuard><Wall/></AuthGuard> },
+        Route::WallQueue => html! { <AuthGuard permission="present"><WallQueue/></AuthGuard> },
         Route::NotFound => html! { <NotFound/> },
     }
 }
@@ -0,0 +1,317 @@
+//! `ALLOW_ORIGIN` rules and a CORS layer per route, each backend passes the headers and credentials it needs
+
+use anyhow::{bail, Context, Result};
+use axum::handler::Handler;
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use axum::extract::Request;
+use axum::response::IntoResponse;
+use axum::routing::{self, MethodRouter, Route};
+use axum::Router;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::{self, Display};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tower::{Layer, Service};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::info;
+use crate::REQUEST_ID_HEADER;
+
+const ENV_KEY: &str = "ALLOW_ORIGIN";
+const REGEX_PREFIX: &str = "regex:";
+/// axum answers HEAD with the GET handler
+pub const GET: &[Method] = &[Method::GET, Method::HEAD];
+pub const POST: &[Method] = &[Method::POST];
+pub const PUT: &[Method] = &[Method::PUT];
+pub const DELETE: &[Method] = &[Method::DELETE];
+
+/// A single entry of `ALLOW_ORIGIN`, entries are separated by commas:
+/// - `https://example.com` only allows exactly this origin
+/// - `https://*.example.com` allows every subdomain, but not `https://example.com` itself
+/// - `regex:https://pr-\d+\.example\.com` allows every origin the regex matches as a whole,
+///   it is anchored at both ends so `https://pr-1.example.com.evil.org` does not match
+#[derive(Debug)]
+enum OriginRule {
+    Exact(String),
+    Subdomain { scheme: String, suffix: String },
+    Pattern(Regex),
+}
+
+#[derive(Debug)]
+pub struct CorsPolicy {
+    rules: Vec<OriginRule>,
+    /// Allowed on top of `Content-Type` and the request id
+    headers: Vec<HeaderName>,
+    credentials: bool,
+}
+
+/// Collects routes with their methods, so the CORS layer of every path allows exactly the
+/// methods registered for it (plus `OPTIONS` for the preflight)
+pub struct CorsRouter<S = ()> {
+    policy: Arc<CorsPolicy>,
+    routes: BTreeMap<&'static str, (Vec<Method>, MethodRouter<S>)>,
+}
+
+impl OriginRule {
+    fn parse(rule: &str) -> Result<Self> {
+        if let Some(pattern) = rule.strip_prefix(REGEX_PREFIX) {
+            let regex = Regex::new(&format!("^(?:{pattern})$"))
+                .with_context(|| format!("Invalid origin regex {pattern}"))?;
+            return Ok(Self::Pattern(regex));
+        }
+
+        let Some((scheme, host)) = rule.split_once("://") else {
+            bail!("Origin {rule} has no scheme");
+        };
+
+        if let Some(suffix) = host.strip_prefix("*.") {
+            if suffix.contains('*') {
+                bail!("Origin {rule} may only contain a single leading wildcard");
+            }
+            return Ok(Self::Subdomain {
+                scheme: scheme.to_string(),
+                suffix: format!(".{suffix}"),
+            });
+        }
+
+        if rule.contains('*') {
+            bail!("Origin {rule} may only contain a wildcard as the first subdomain");
+        }
+        rule.parse::<HeaderValue>()
+            .with_context(|| format!("Failed to parse origin {rule}"))?;
+
+        Ok(Self::Exact(rule.trim_end_matches('/').to_string()))
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        match self {
+            Self::Exact(exact) => origin == exact,
+            Self::Subdomain { scheme, suffix } => {
+                origin.split_once("://")
+                      .filter(|(origin_scheme, _)| origin_scheme == scheme)
+                      .and_then(|(_, host)| host.strip_suffix(suffix.as_str()))
+                      .is_some_and(|sub| !sub.is_empty() && !sub.contains('/'))
+            }
+            Self::Pattern(regex) => regex.is_match(origin),
+        }
+    }
+}
+
+impl Display for OriginRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(exact) => write!(f, "{exact}"),
+            Self::Subdomain { scheme, suffix } => write!(f, "{scheme}://*{suffix}"),
+            Self::Pattern(regex) => write!(f, "{REGEX_PREFIX}{regex}"),
+        }
+    }
+}
+
+impl CorsPolicy {
+    pub fn from_env() -> Result<Self> {
+        let origins = env::var(ENV_KEY)
+            .with_context(|| format!("{ENV_KEY} env var is not set"))?;
+
+        Self::parse(&origins)
+    }
+
+    /// Parses a comma separated list of origin rules, see [`OriginRule`]
+    pub fn parse(origins: &str) -> Result<Self> {
+        let rules = origins.split(',')
+                           .map(str::trim)
+                           .filter(|rule| !rule.is_empty())
+                           .map(OriginRule::parse)
+                           .collect::<Result<Vec<_>>>()?;
+
+        if rules.is_empty() {
+            bail!("{ENV_KEY} does not contain any origin");
+        }
+
+        Ok(Self { rules, headers: vec![], credentials: false })
+    }
+
+    /// Request headers a backend needs on top of `Content-Type` and the request id, e.g. `Authorization`
+    pub fn allow_headers(mut self, headers: &[HeaderName]) -> Self {
+        self.headers.extend_from_slice(headers);
+        self
+    }
+
+    /// Whether the browser may send cookies cross origin, off by default
+    pub fn allow_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    pub fn allows(&self, origin: &HeaderValue) -> bool {
+        origin.to_str()
+              .is_ok_and(|origin| self.rules.iter().any(|rule| rule.matches(origin)))
+    }
+
+    pub fn router<S>(self) -> CorsRouter<S> {
+        CorsRouter { policy: Arc::new(self), routes: BTreeMap::new() }
+    }
+
+    fn layer(self: &Arc<Self>, methods: &[Method]) -> CorsLayer {
+        let policy = Arc::clone(self);
+        let mut methods = methods.to_vec();
+        methods.push(Method::OPTIONS);
+
+        let mut headers = vec![header::CONTENT_TYPE, REQUEST_ID_HEADER];
+        headers.extend_from_slice(&self.headers);
+
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::predicate(move |origin, _| policy.allows(origin)))
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .expose_headers([REQUEST_ID_HEADER])
+            .allow_credentials(self.credentials)
+    }
+}
+
+impl<S> CorsRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    pub fn get<H, T>(self, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route(path, GET, routing::get(handler))
+    }
+
+    pub fn post<H, T>(self, path: &'static str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route(path, POST, routing::post(handler))
+    }
+
+    /// For routes that need their own layers, `methods` has to match the `method_router`
+    pub fn route(mut self, path: &'static str, methods: &[Method], method_router: MethodRouter<S>) -> Self {
+        let (mut known, existing) = self.routes
+                                        .remove(path)
+                                        .unwrap_or_else(|| (vec![], MethodRouter::new()));
+        known.extend_from_slice(methods);
+        self.routes.insert(path, (known, existing.merge(method_router)));
+        self
+    }
+
+    /// Like [`Router::route_layer`], only applies to the routes added before
+    pub fn route_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request, Error = Infallible> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.routes = self.routes
+                          .into_iter()
+                          .map(|(path, (methods, method_router))| {
+                              (path, (methods, method_router.route_layer(layer.clone())))
+                          })
+                          .collect();
+        self
+    }
+
+    pub fn into_router(self) -> Router<S> {
+        let Self { policy, routes } = self;
+
+        info!("CORS allows origins [{}]", policy.rules
+                                                .iter()
+                                                .map(ToString::to_string)
+                                                .collect::<Vec<_>>()
+                                                .join(", "));
+
+        routes.into_iter()
+              .fold(Router::new(), |router, (path, (methods, method_router))| {
+                  info!("CORS allows {methods:?} on {path}");
+                  router.route(path, method_router.layer(policy.layer(&methods)))
+              })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    fn assert_matches(rule: &str, allowed: &[&str], denied: &[&str]) {
+        let rule = OriginRule::parse(rule).unwrap();
+        for origin in allowed {
+            assert!(rule.matches(origin), "{rule} should allow {origin}");
+        }
+        for origin in denied {
+            assert!(!rule.matches(origin), "{rule} should not allow {origin}");
+        }
+    }
+
+    #[test]
+    fn matches_exact_origins() {
+        assert_matches(
+            "https://example.com/",
+            &["https://example.com"],
+            &["http://example.com", "https://example.com.evil.org", "https://sub.example.com"],
+        );
+    }
+
+    #[test]
+    fn matches_subdomains() {
+        assert_matches(
+            "https://*.example.com",
+            &["https://a.example.com", "https://a.b.example.com"],
+            &["https://example.com", "http://a.example.com", "https://evilexample.com", "https://a.example.com/path"],
+        );
+    }
+
+    #[test]
+    fn anchors_patterns() {
+        assert_matches(
+            r"regex:https://pr-\d+\.example\.com",
+            &["https://pr-1.example.com", "https://pr-42.example.com"],
+            &["https://pr-1.example.com.evil.org", "https://evil.org/https://pr-1.example.com", "https://pr-x.example.com"],
+        );
+        // Alternatives must not escape the anchors
+        assert_matches(
+            "regex:https://a.example.com|https://b.example.com",
+            &["https://a.example.com", "https://b.example.com"],
+            &["https://a.example.com.evil.org", "https://evil.org/https://b.example.com"],
+        );
+    }
+
+    async fn preflight(policy: CorsPolicy) -> axum::http::HeaderMap {
+        let app = policy.router().post("/feedback", || async {}).into_router();
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/feedback")
+            .header(header::ORIGIN, "https://example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        app.oneshot(req).await.unwrap().headers().clone()
+    }
+
+    #[tokio::test]
+    async fn allows_only_the_requested_headers_and_credentials() {
+        let headers = preflight(CorsPolicy::parse("https://example.com").unwrap()).await;
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_HEADERS], "content-type,x-request-id");
+        assert!(!headers.contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+
+        let policy = CorsPolicy::parse("https://example.com")
+            .unwrap()
+            .allow_headers(&[header::AUTHORIZATION])
+            .allow_credentials(true);
+        let headers = preflight(policy).await;
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_HEADERS], "content-type,x-request-id,authorization");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        for rule in ["example.com", "https://a.*.example.com", "https://*.*.example.com", "regex:("] {
+            assert!(OriginRule::parse(rule).is_err(), "{rule} should be rejected");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Liveness, readiness and build info, each backend only brings its own storage check
+
+use anyhow::{bail, Context, Result};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::DateTime;
+use serde::Serialize;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::{debug, error};
+
+/// What `/version` reports about the binary, the git hash and build time are filled in here
+#[derive(Debug, Clone, Copy)]
+pub struct Service {
+    /// `env!("CARGO_PKG_NAME")` of the binary
+    pub name: &'static str,
+    /// `env!("CARGO_PKG_VERSION")` of the binary
+    pub version: &'static str,
+    pub storage_mode: &'static str,
+    pub storage_root: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Readiness {
+    ready: bool,
+    reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct VersionInfo {
+    name: &'static str,
+    version: &'static str,
+    git_hash: &'static str,
+    build_time: String,
+    storage_mode: &'static str,
+    storage_root: &'static str,
+}
+
+pub async fn healthz() -> impl IntoResponse {
+    (StatusCode::OK, "OK")
+}
+
+/// 200 if the storage `check` passed, 503 otherwise, `state` is what was checked, e.g. `writable`
+pub fn readiness(check: io::Result<()>, root: &str, state: &str) -> impl IntoResponse {
+    match check {
+        Ok(()) => {
+            debug!("Storage {root} is {state}");
+            (StatusCode::OK, Json(Readiness { ready: true, reason: None }))
+        }
+        Err(e) => {
+            error!("Storage {root} is not {state}: {e}");
+            (StatusCode::SERVICE_UNAVAILABLE, Json(Readiness {
+                ready: false,
+                reason: Some(format!("Storage {root} is not {state}")),
+            }))
+        }
+    }
+}
+
+pub fn version(service: Service) -> impl IntoResponse {
+    let build_time = env!("BUILD_TIME")
+        .parse()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_else(|| String::from("unknown"));
+
+    Json(VersionInfo {
+        name: service.name,
+        version: service.version,
+        git_hash: env!("GIT_HASH"),
+        build_time,
+        storage_mode: service.storage_mode,
+        storage_root: service.storage_root,
+    })
+}
+
+/// Used as the docker healthcheck, the slim image has no curl
+pub async fn check(port: u16) -> Result<()> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect(addr)
+        .await
+        .with_context(|| format!("Failed to connect to {addr}"))?;
+
+    stream.write_all(b"GET /readyz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+          .await
+          .context("Failed to send request")?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)
+          .await
+          .context("Failed to read response")?;
+
+    let status_line = response.lines().next().unwrap_or_default();
+    if !status_line.contains(" 200 ") {
+        bail!("Unhealthy: {status_line}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+//! Logging, request ids, the optional OTLP span export, health routes, metrics and CORS shared by both backends
+
+use anyhow::{Context, Result};
+use axum::extract::Request;
+use axum::http::HeaderName;
+use axum::Router;
+use std::env;
+use std::sync::LazyLock;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::{DefaultOnResponse, TraceLayer};
+use tracing::{info_span, Level};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, Layer};
+
+pub mod cors;
+pub mod health;
+pub mod metrics;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+#[derive(Debug, Clone, Copy)]
+enum LogFormat {
+    Full,
+    Pretty,
+    Json,
+}
+
+static LOG_FORMAT: LazyLock<LogFormat> = LazyLock::new(|| {
+    const ENV_KEY: &str = "LOG_FORMAT";
+    const DEFAULT_FORMAT: LogFormat = LogFormat::Full;
+
+    match env::var(ENV_KEY).as_deref() {
+        Ok("full") => LogFormat::Full,
+        Ok("pretty") => LogFormat::Pretty,
+        Ok("json") => LogFormat::Json,
+        Ok(_) => {
+            println!("WARNING: {ENV_KEY} is set, but the value is invalid, \
+                using default ({DEFAULT_FORMAT:?})");
+            DEFAULT_FORMAT
+        }
+        Err(_) => DEFAULT_FORMAT,
+    }
+});
+
+/// Flushes the remaining spans to the collector when dropped, keep it alive until `main` returns
+pub struct TelemetryGuard {
+    #[cfg(feature = "otlp")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = self.provider.take()
+            && let Err(e) = provider.shutdown() {
+            println!("WARNING: Failed to shut down the OTLP exporter: {e}");
+        }
+    }
+}
+
+/// `service` names the spans exported over OTLP, e.g. `env!("CARGO_PKG_NAME")` of the binary
+#[cfg_attr(not(feature = "otlp"), allow(unused_variables))]
+pub fn init(level: Level, service: &'static str) -> Result<TelemetryGuard> {
+    let fmt_layer = match *LOG_FORMAT {
+        LogFormat::Full => fmt::layer().boxed(),
+        LogFormat::Pretty => fmt::layer().pretty().boxed(),
+        LogFormat::Json => fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+    };
+
+    #[cfg(feature = "otlp")]
+    let provider = otlp::provider(service)?;
+    #[cfg(feature = "otlp")]
+    let otlp_layer = provider.as_ref().map(|provider| otlp::layer(provider, service));
+    #[cfg(not(feature = "otlp"))]
+    let otlp_layer: Option<LevelFilter> = None;
+
+    tracing_subscriber::registry()
+        .with(LevelFilter::from_level(level))
+        .with(fmt_layer)
+        .with(otlp_layer)
+        .try_init()
+        .with_context(|| format!("Failed to set up logging with level {level} and format {:?}", *LOG_FORMAT))?;
+
+    Ok(TelemetryGuard {
+        #[cfg(feature = "otlp")]
+        provider,
+    })
+}
+
+/// Gives every request an `X-Request-Id` (or keeps the one the client sent),
+/// logs it in the request span and sends it back in the response
+pub fn trace_requests(app: Router) -> Router {
+    app.layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER))
+       .layer(
+           TraceLayer::new_for_http()
+               .make_span_with(|req: &Request| {
+                   let request_id = req.headers()
+                                       .get(REQUEST_ID_HEADER)
+                                       .and_then(|id| id.to_str().ok())
+                                       .unwrap_or_default();
+                   info_span!("request", method = %req.method(), uri = %req.uri(), request_id)
+               })
+               .on_response(DefaultOnResponse::new().level(Level::INFO))
+       )
+       .layer(SetRequestIdLayer::new(REQUEST_ID_HEADER, MakeRequestUuid))
+}
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use anyhow::{Context, Result};
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_otlp::SpanExporter;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use opentelemetry_sdk::Resource;
+    use std::env;
+    use tracing::Subscriber;
+    use tracing_opentelemetry::OpenTelemetryLayer;
+    use tracing_subscriber::registry::LookupSpan;
+
+    const ENV_KEY: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+    /// Export is only enabled if the collector endpoint is set,
+    /// e.g. `OTEL_EXPORTER_OTLP_ENDPOINT=http://localhost:4318`
+    pub fn provider(service: &'static str) -> Result<Option<SdkTracerProvider>> {
+        if env::var(ENV_KEY).is_err() {
+            return Ok(None);
+        }
+
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .build()
+            .context("Failed to build OTLP exporter")?;
+
+        Ok(Some(
+            SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_resource(Resource::builder().with_service_name(service).build())
+                .build()
+        ))
+    }
+
+    pub fn layer<S>(provider: &SdkTracerProvider, service: &'static str) -> OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(service))
+    }
+}
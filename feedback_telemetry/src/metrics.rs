@@ -0,0 +1,67 @@
+//! `/metrics` and where it is served, each backend registers its own metrics in [`REGISTRY`]
+
+use anyhow::{Context, Result};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use prometheus::{Encoder, Registry, TextEncoder};
+use std::env;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::LazyLock;
+use tokio::net::TcpListener;
+use tracing::{error, info};
+
+pub static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+/// If set, `/metrics` is only served on this port instead of the public one.
+/// An invalid value fails startup instead of exposing the metrics publicly.
+fn metrics_port() -> Result<Option<u16>> {
+    const ENV_KEY: &str = "METRICS_PORT";
+
+    match env::var(ENV_KEY) {
+        Ok(port) => port.parse()
+                        .map(Some)
+                        .with_context(|| format!("{ENV_KEY} is set, but {port:?} is not a valid port")),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Serves `/metrics` on `METRICS_PORT` if it is set, otherwise adds it to `app`
+pub async fn mount(app: Router) -> Result<Router> {
+    let Some(metrics_port) = metrics_port()? else {
+        return Ok(app.merge(router()));
+    };
+
+    let listener = TcpListener::bind(
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), metrics_port)
+    )
+        .await
+        .with_context(|| format!("Failed to bind metrics port {metrics_port}"))?;
+
+    info!("Serving metrics on port {metrics_port}");
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router()).await {
+            error!("Metrics server on port {metrics_port} failed: {e}");
+        }
+    });
+
+    Ok(app)
+}
+
+fn router() -> Router {
+    Router::new().route("/metrics", get(metrics))
+}
+
+async fn metrics() -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+
+    if let Err(e) = encoder.encode(&REGISTRY.gather(), &mut buffer) {
+        error!("Failed to encode metrics: {e}");
+        return (StatusCode::INTERNAL_SERVER_ERROR, [(header::CONTENT_TYPE, "text/plain")], vec![]);
+    }
+
+    (StatusCode::OK, [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], buffer)
+}